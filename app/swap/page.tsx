@@ -102,6 +102,7 @@ export default function SwapPage() {
         headers: { "Content-Type": "application/json" },
         body: JSON.stringify({
           dao: daoAddress,
+          member: publicKey.toBase58(),
           serializedTransaction,
           encoding: "base64",
         }),
@@ -156,6 +156,7 @@ function ProjectCard({ project, connection, wallet }: ProjectCardProps) {
         headers: { "Content-Type": "application/json" },
         body: JSON.stringify({
           dao: project.dao ?? project.projectId,
+          member: wallet.publicKey?.toBase58(),
           serializedTransaction: draft.serialized,
           encoding: draft.encoding
         })
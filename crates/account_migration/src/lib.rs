@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Reads the layout version of a stored account. Layouts from before the version
+/// byte are recognised by `legacy_len` and report version 1.
+pub fn stored_version<T: AccountDeserialize>(
+    data: &[u8],
+    legacy_len: usize,
+    version: fn(&T) -> u8,
+) -> Result<u8> {
+    if data.len() == legacy_len {
+        return Ok(1);
+    }
+    Ok(version(&T::try_deserialize(&mut &data[..])?))
+}
+
+/// Grows a legacy account to `new_len`, topping up rent from `payer`, and
+/// overwrites it with the migrated layout.
+pub fn write_migrated_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+    migrated: &T,
+) -> Result<()> {
+    let required_lamports = Rent::get()?.minimum_balance(new_len);
+    let current_lamports = account.lamports();
+    if required_lamports > current_lamports {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: account.clone(),
+            },
+        );
+        system_program::transfer(cpi_ctx, required_lamports - current_lamports)?;
+    }
+
+    account.realloc(new_len, false)?;
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    migrated.try_serialize(&mut writer)?;
+
+    Ok(())
+}
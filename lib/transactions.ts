@@ -76,6 +76,8 @@ export async function buildInitializeProjectTx({
 
   const projectVault = getAssociatedTokenAddressSync(mintKey, projectPda, true);
 
+  const [config] = findConfigPda();
+
   const badgeMint = Keypair.generate();
   const [badgeMetadata] = PublicKey.findProgramAddressSync(
     [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), badgeMint.publicKey.toBuffer()],
@@ -91,6 +93,7 @@ export async function buildInitializeProjectTx({
       projectVault,
       badgeMint: badgeMint.publicKey,
       badgeMetadata,
+      config,
       systemProgram: SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -120,6 +123,8 @@ export type BuildPledgeParams = {
   wallet: WalletContextState;
   project: string | PublicKey;
   amount: number | bigint | string;
+  /** Wallet that referred the donor; must have registered for this project. */
+  referrer?: string | PublicKey | null;
 };
 
 export type BuildPledgeResult = {
@@ -127,13 +132,15 @@ export type BuildPledgeResult = {
   project: PublicKey;
   donorTokenAccount: PublicKey;
   donorBadgeAccount: PublicKey;
+  donation: PublicKey;
 };
 
 export async function buildPledgeTx({
   connection,
   wallet,
   project,
-  amount
+  amount,
+  referrer
 }: BuildPledgeParams): Promise<BuildPledgeResult> {
   const provider = createAnchorProvider(connection, wallet);
   const program = new Program<FundingHubIdl>(fundingHubIdl as FundingHubIdl, provider);
@@ -149,6 +156,24 @@ export async function buildPledgeTx({
 
   const donorTokenAccount = getAssociatedTokenAddressSync(projectMint, donor);
   const donorBadgeAccount = getAssociatedTokenAddressSync(badgeMint, donor);
+  const [donation] = PublicKey.findProgramAddressSync(
+    [Buffer.from("donation"), projectPubkey.toBuffer(), donor.toBuffer()],
+    FUNDING_HUB_PROGRAM_ID
+  );
+  const [config] = findConfigPda();
+  const badgeAccounts = await findDonorBadgeAccounts(program, projectPubkey, donor, donation);
+
+  const referrerKey = referrer
+    ? typeof referrer === "string"
+      ? new PublicKey(referrer)
+      : referrer
+    : null;
+  const referral = referrerKey
+    ? PublicKey.findProgramAddressSync(
+        [Buffer.from("referral"), projectPubkey.toBuffer(), referrerKey.toBuffer()],
+        FUNDING_HUB_PROGRAM_ID
+      )[0]
+    : null;
 
   const setupInstructions: TransactionInstruction[] = [];
   const donorAccountInfo = await connection.getAccountInfo(donorTokenAccount);
@@ -159,7 +184,7 @@ export async function buildPledgeTx({
   }
 
   const pledgeInstruction = await program.methods
-    .pledge(new BN(amount.toString()))
+    .pledge(new BN(amount.toString()), referrerKey)
     .accounts({
       donor,
       donorTokenAccount,
@@ -167,6 +192,11 @@ export async function buildPledgeTx({
       projectVault,
       badgeMint,
       donorBadgeAccount,
+      donation,
+      referral,
+      rewardTier: null,
+      ...badgeAccounts,
+      config,
       tokenProgram: TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
       associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -187,7 +217,8 @@ export async function buildPledgeTx({
     transaction: tx,
     project: projectPubkey,
     donorTokenAccount,
-    donorBadgeAccount
+    donorBadgeAccount,
+    donation
   };
 }
 
@@ -225,6 +256,51 @@ export async function buildDaoSponsorDepositTx({
   return tx;
 }
 
+/** Donor badge accounts `pledge` needs to refresh an existing badge; null otherwise. */
+async function findDonorBadgeAccounts(
+  program: Program<FundingHubIdl>,
+  project: PublicKey,
+  donor: PublicKey,
+  donation: PublicKey
+) {
+  // @ts-ignore - TypeScript can't infer account types from JSON IDL in Anchor 0.30.1
+  const donationAccount = await (program.account as any).donation.fetchNullable(donation);
+  if (!donationAccount?.badgeMinted) {
+    return {
+      badgeConfig: null,
+      donorBadgeMint: null,
+      donorBadgeMetadata: null,
+      tokenMetadataProgram: null,
+      sysvarInstructions: null
+    };
+  }
+
+  const [badgeConfig] = PublicKey.findProgramAddressSync(
+    [Buffer.from("badge_config"), project.toBuffer()],
+    FUNDING_HUB_PROGRAM_ID
+  );
+  const [donorBadgeMint] = PublicKey.findProgramAddressSync(
+    [Buffer.from("donor_badge"), project.toBuffer(), donor.toBuffer()],
+    FUNDING_HUB_PROGRAM_ID
+  );
+  const [donorBadgeMetadata] = PublicKey.findProgramAddressSync(
+    [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), donorBadgeMint.toBuffer()],
+    TOKEN_METADATA_PROGRAM_ID
+  );
+
+  return {
+    badgeConfig,
+    donorBadgeMint,
+    donorBadgeMetadata,
+    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
+  };
+}
+
+function findConfigPda(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([Buffer.from("config")], FUNDING_HUB_PROGRAM_ID);
+}
+
 function createAnchorProvider(connection: Connection, wallet: WalletContextState): AnchorProvider {
   if (!wallet.signTransaction || !wallet.signAllTransactions) {
     throw new Error("Wallet adapter is missing signTransaction/signAllTransactions implementations");
@@ -1,6 +1,13 @@
+use account_migration::{stored_version, write_migrated_account};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::Discriminator;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use anchor_spl::token::{
+    self, Burn, CloseAccount, FreezeAccount, Mint, MintTo, ThawAccount, Token, TokenAccount,
+    Transfer,
+};
+use program::DaoPass;
 
 declare_id!("Dz9WAiHQDGLK8K8puZosdUux3UAJMRTKTfWJFqRve4Dk");
 
@@ -8,13 +15,79 @@ declare_id!("Dz9WAiHQDGLK8K8puZosdUux3UAJMRTKTfWJFqRve4Dk");
 pub mod dao_pass {
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>, guardian: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.guardian = guardian;
+        config.paused = 0;
+        config.bump = ctx.bumps.config;
+
+        Ok(())
+    }
+
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.config.guardian = guardian;
+
+        Ok(())
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, paused: u16) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, DaoError::InvalidPauseFlags);
+        ctx.accounts.config.paused = paused;
+
+        Ok(())
+    }
+
+    pub fn migrate_dao(ctx: Context<MigrateDao>) -> Result<()> {
+        let dao_info = ctx.accounts.dao.to_account_info();
+        let migrated = {
+            let data = dao_info.try_borrow_data()?;
+            match stored_version(&data, DaoV1::LEN, |dao: &Dao| dao.version)? {
+                1 => DaoV1::try_from_bytes(&data)?.migrate(),
+                Dao::VERSION => return err!(DaoError::AlreadyMigrated),
+                _ => return err!(DaoError::UnsupportedVersion),
+            }
+        };
+
+        write_migrated_account(
+            &dao_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Dao::LEN,
+            &migrated,
+        )
+    }
+
+    pub fn migrate_member(ctx: Context<MigrateMember>) -> Result<()> {
+        let member_info = ctx.accounts.member.to_account_info();
+        let migrated = {
+            let data = member_info.try_borrow_data()?;
+            match stored_version(&data, MemberV1::LEN, |member: &Member| member.version)? {
+                1 => MemberV1::try_from_bytes(&data)?.migrate(),
+                Member::VERSION => return err!(DaoError::AlreadyMigrated),
+                _ => return err!(DaoError::UnsupportedVersion),
+            }
+        };
+
+        write_migrated_account(
+            &member_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Member::LEN,
+            &migrated,
+        )
+    }
+
     pub fn initialize_dao(
         ctx: Context<InitializeDao>,
         name_seed: [u8; 8],
         sponsor_mint: Pubkey,
         max_relay_spend: u64,
+        relay_window_secs: u64,
+        relay_window: RelayWindow,
     ) -> Result<()> {
         require!(max_relay_spend > 0, DaoError::InvalidLimit);
+        require!(relay_window_secs > 0, DaoError::InvalidDuration);
 
         let dao = &mut ctx.accounts.dao;
         dao.authority = ctx.accounts.authority.key();
@@ -22,11 +95,14 @@ pub mod dao_pass {
         dao.sponsor_mint = sponsor_mint;
         dao.sponsor_vault = ctx.accounts.sponsor_vault.key();
         dao.max_relay_spend = max_relay_spend;
-        dao.relay_epoch = Clock::get()?.slot;
+        dao.relay_epoch = relay_now(&Clock::get()?);
         dao.relay_spent = 0;
+        dao.relay_window_secs = relay_window_secs;
+        dao.relay_window = relay_window;
         dao.total_members = 0;
         dao.bump = ctx.bumps.dao;
         dao.name_seed = name_seed;
+        dao.version = Dao::VERSION;
 
         Ok(())
     }
@@ -56,6 +132,19 @@ pub mod dao_pass {
             ]
         ];
 
+        require!(!ctx.accounts.member.revoked, DaoError::MemberRevoked);
+
+        // Passes are soulbound: thaw for a repeat mint, then freeze again
+        if ctx.accounts.member_pass_account.is_frozen() {
+            thaw_pass(
+                &ctx.accounts.token_program,
+                &ctx.accounts.member_pass_account,
+                &ctx.accounts.pass_mint,
+                &ctx.accounts.dao.to_account_info(),
+                signer_seeds,
+            )?;
+        }
+
         let mint_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             MintTo {
@@ -68,6 +157,14 @@ pub mod dao_pass {
 
         token::mint_to(mint_ctx, 1)?;
 
+        freeze_pass(
+            &ctx.accounts.token_program,
+            &ctx.accounts.member_pass_account,
+            &ctx.accounts.pass_mint,
+            &ctx.accounts.dao.to_account_info(),
+            signer_seeds,
+        )?;
+
         let member = &mut ctx.accounts.member;
         if member.membership_mint == Pubkey::default() {
             // This is a NEW member
@@ -76,6 +173,8 @@ pub mod dao_pass {
             member.membership_mint = ctx.accounts.pass_mint.key();
             member.member_pass_account = ctx.accounts.member_pass_account.key();
             member.joined_ts = Clock::get()?.unix_timestamp;
+            member.expires_ts = ctx.accounts.dao.expiry_from(member.joined_ts)?;
+            member.version = Member::VERSION;
 
             // Only increment count for NEW members
             let dao = &mut ctx.accounts.dao;
@@ -94,197 +193,2412 @@ pub mod dao_pass {
         Ok(())
     }
 
-    pub fn configure_sponsor(ctx: Context<ConfigureSponsor>, new_cap: u64) -> Result<()> {
-        require!(new_cap > 0, DaoError::InvalidLimit);
+    /// Chooses how wallets become members without the authority minting each pass.
+    pub fn set_join_policy(
+        ctx: Context<SetJoinPolicy>,
+        join_policy: JoinPolicy,
+        allowlist_root: [u8; 32],
+    ) -> Result<()> {
+        if join_policy == JoinPolicy::Allowlist {
+            require!(allowlist_root != [0u8; 32], DaoError::InvalidAllowlistRoot);
+        }
 
         let dao = &mut ctx.accounts.dao;
-        dao.max_relay_spend = new_cap;
-        dao.sponsor_vault = ctx.accounts.sponsor_vault.key();
+        dao.join_policy = join_policy;
+        dao.allowlist_root = allowlist_root;
 
         Ok(())
     }
 
-    pub fn record_relay_spend(ctx: Context<RecordRelaySpend>, spend: u64) -> Result<()> {
-        require!(spend > 0, DaoError::InvalidSpend);
+    /// Files an application to join a DAO whose policy is `Application`.
+    pub fn request_join(ctx: Context<RequestJoin>) -> Result<()> {
+        require!(
+            ctx.accounts.dao.join_policy == JoinPolicy::Application,
+            DaoError::JoinPolicyMismatch
+        );
+
+        let join_request = &mut ctx.accounts.join_request;
+        join_request.dao = ctx.accounts.dao.key();
+        join_request.applicant = ctx.accounts.applicant.key();
+        join_request.requested_ts = Clock::get()?.unix_timestamp;
+        join_request.approved = false;
+        join_request.bump = ctx.bumps.join_request;
+
+        Ok(())
+    }
+
+    /// Approves or rejects pending applications in one go. Rejected requests are
+    /// closed and their rent returned to the applicant.
+    pub fn review_join_requests<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReviewJoinRequests<'info>>,
+        decisions: Vec<bool>,
+    ) -> Result<()> {
+        require!(
+            ctx.remaining_accounts.len() == decisions.len() * 2,
+            DaoError::JoinRequestAccountsMismatch
+        );
+
+        let dao_key = ctx.accounts.dao.key();
+        for (approve, accounts) in decisions
+            .iter()
+            .zip(ctx.remaining_accounts.chunks_exact(2))
+        {
+            let mut join_request = Account::<JoinRequest>::try_from(&accounts[0])?;
+            let applicant = &accounts[1];
+            require_keys_eq!(join_request.dao, dao_key, DaoError::JoinRequestAccountsMismatch);
+            require_keys_eq!(
+                join_request.applicant,
+                applicant.key(),
+                DaoError::JoinRequestAccountsMismatch
+            );
+            require!(!join_request.approved, DaoError::JoinRequestAlreadyReviewed);
+
+            if *approve {
+                join_request.approved = true;
+                join_request.exit(&crate::ID)?;
+            } else {
+                join_request.close(applicant.clone())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Self-serve join under the DAO's policy. The applicant pays one term of dues,
+    /// if any, and receives a frozen pass without the authority co-signing.
+    pub fn join_dao(ctx: Context<JoinDao>, allowlist_proof: Vec<[u8; 32]>) -> Result<()> {
+        let dao = &ctx.accounts.dao;
+        let applicant = ctx.accounts.applicant.key();
+
+        match dao.join_policy {
+            JoinPolicy::AuthorityOnly => return err!(DaoError::JoinPolicyMismatch),
+            JoinPolicy::Open => {}
+            JoinPolicy::Allowlist => {
+                let leaf = keccak::hash(applicant.as_ref()).to_bytes();
+                require!(
+                    verify_merkle_proof(&allowlist_proof, dao.allowlist_root, leaf),
+                    DaoError::NotAllowlisted
+                );
+            }
+            JoinPolicy::Application => {
+                let join_request = ctx
+                    .accounts
+                    .join_request
+                    .as_ref()
+                    .ok_or(DaoError::JoinRequestNotApproved)?;
+                require!(join_request.approved, DaoError::JoinRequestNotApproved);
+            }
+        }
+
+        if dao.dues_amount > 0 {
+            let payer_token_account = ctx
+                .accounts
+                .payer_token_account
+                .as_ref()
+                .ok_or(DaoError::DuesAccountsMissing)?;
+            let dues_treasury = ctx
+                .accounts
+                .dues_treasury
+                .as_ref()
+                .ok_or(DaoError::DuesAccountsMissing)?;
+            require_keys_eq!(payer_token_account.mint, dao.sponsor_mint, DaoError::MintMismatch);
+
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: payer_token_account.to_account_info(),
+                    to: dues_treasury.to_account_info(),
+                    authority: ctx.accounts.applicant.to_account_info(),
+                },
+            );
+            token::transfer(transfer_ctx, dao.dues_amount)?;
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Dao::SEED_PREFIX,
+                dao.authority.as_ref(),
+                &dao.name_seed,
+                &[dao.bump],
+            ]
+        ];
+
+        let mint_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.pass_mint.to_account_info(),
+                to: ctx.accounts.member_pass_account.to_account_info(),
+                authority: ctx.accounts.dao.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(mint_ctx, 1)?;
+
+        freeze_pass(
+            &ctx.accounts.token_program,
+            &ctx.accounts.member_pass_account,
+            &ctx.accounts.pass_mint,
+            &ctx.accounts.dao.to_account_info(),
+            signer_seeds,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let member = &mut ctx.accounts.member;
+        member.dao = ctx.accounts.dao.key();
+        member.owner = applicant;
+        member.membership_mint = ctx.accounts.pass_mint.key();
+        member.member_pass_account = ctx.accounts.member_pass_account.key();
+        member.joined_ts = now;
+        member.expires_ts = ctx.accounts.dao.expiry_from(now)?;
+        member.mint_count = 1;
+        member.version = Member::VERSION;
 
         let dao = &mut ctx.accounts.dao;
-        let clock = Clock::get()?;
+        dao.total_members = dao
+            .total_members
+            .checked_add(1)
+            .ok_or(DaoError::MathOverflow)?;
+
+        if let Some(join_request) = ctx.accounts.join_request.as_ref() {
+            join_request.close(ctx.accounts.applicant.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes a member: freezes their pass so it can no longer move and flags the
+    /// `Member` record so relayers and governance stop treating them as a member.
+    pub fn revoke_pass(ctx: Context<RevokePass>, reason_hash: [u8; 32]) -> Result<()> {
+        require!(!ctx.accounts.member.revoked, DaoError::MemberRevoked);
 
-        if clock.slot.saturating_sub(dao.relay_epoch) > RELAY_EPOCH_SLOTS {
-            dao.relay_epoch = clock.slot;
-            dao.relay_spent = 0;
+        // Passes issued before they were soulbound may still be unfrozen
+        if !ctx.accounts.member_pass_account.is_frozen() {
+            let dao = &ctx.accounts.dao;
+            let signer_seeds: &[&[&[u8]]] = &[
+                &[
+                    Dao::SEED_PREFIX,
+                    dao.authority.as_ref(),
+                    &dao.name_seed,
+                    &[dao.bump],
+                ]
+            ];
+
+            freeze_pass(
+                &ctx.accounts.token_program,
+                &ctx.accounts.member_pass_account,
+                &ctx.accounts.pass_mint,
+                &ctx.accounts.dao.to_account_info(),
+                signer_seeds,
+            )?;
         }
 
-        dao.relay_spent = dao
-            .relay_spent
-            .checked_add(spend)
+        let member = &mut ctx.accounts.member;
+        member.revoked = true;
+        member.revoked_ts = Clock::get()?.unix_timestamp;
+        member.revoke_reason_hash = reason_hash;
+
+        let dao = &mut ctx.accounts.dao;
+        dao.total_members = dao
+            .total_members
+            .checked_sub(1)
             .ok_or(DaoError::MathOverflow)?;
-        require!(dao.relay_spent <= dao.max_relay_spend, DaoError::RelayBudgetExceeded);
 
         Ok(())
     }
-}
 
-pub const RELAY_EPOCH_SLOTS: u64 = 432_000; // ~12 hours on Solana (~0.4s per slot)
+    /// Moves a membership to `new_wallet`. The authority can do this alone, so a lost
+    /// or compromised key can be replaced; the old passes then stay frozen where they
+    /// are. If the old wallet co-signs, its passes are burned and the rent goes back to
+    /// it. The same number of passes is minted, frozen, to the new wallet.
+    pub fn migrate_member_wallet(ctx: Context<MigrateMemberWallet>) -> Result<()> {
+        let old_member = &ctx.accounts.member;
+        require!(!old_member.revoked, DaoError::MemberRevoked);
 
-#[derive(Accounts)]
-#[instruction(name_seed: [u8; 8])]
-pub struct InitializeDao<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(
-        init,
-        payer = authority,
-        space = Dao::LEN,
-        seeds = [Dao::SEED_PREFIX, authority.key().as_ref(), &name_seed],
-        bump
-    )]
-    pub dao: Account<'info, Dao>,
-    #[account(
-        init,
-        payer = authority,
-        mint::decimals = 0,
-        mint::authority = dao,
-        mint::freeze_authority = dao
-    )]
-    pub pass_mint: Account<'info, Mint>,
-    /// CHECK: Sponsor vault can be any account controlled by the DAO authority
-    pub sponsor_vault: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
-}
+        let dao = &ctx.accounts.dao;
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Dao::SEED_PREFIX,
+                dao.authority.as_ref(),
+                &dao.name_seed,
+                &[dao.bump],
+            ]
+        ];
 
-#[derive(Accounts)]
-pub struct IssuePass<'info> {
-    pub authority: Signer<'info>,
-    #[account(mut)]
-    pub member_wallet: Signer<'info>,
-    #[account(
-        mut,
-        has_one = authority @ DaoError::Unauthorized,
-        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
-        bump = dao.bump
-    )]
-    pub dao: Account<'info, Dao>,
-    #[account(mut)]
-    pub pass_mint: Account<'info, Mint>,
-    #[account(
-        init_if_needed,
-        payer = member_wallet,
-        space = Member::LEN,
-        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), member_wallet.key().as_ref()],
-        bump
-    )]
-    pub member: Account<'info, Member>,
-    #[account(
-        init_if_needed,
-        payer = member_wallet,
-        associated_token::mint = pass_mint,
-        associated_token::authority = member_wallet
-    )]
-    pub member_pass_account: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
+        let passes = ctx.accounts.old_pass_account.amount;
+        let frozen = ctx.accounts.old_pass_account.is_frozen();
+        if let Some(old_wallet) = ctx.accounts.old_wallet.as_ref() {
+            // Frozen accounts can't burn, so thaw the soulbound pass first
+            if frozen {
+                thaw_pass(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.old_pass_account,
+                    &ctx.accounts.pass_mint,
+                    &ctx.accounts.dao.to_account_info(),
+                    signer_seeds,
+                )?;
+            }
 
-#[derive(Accounts)]
-pub struct ConfigureSponsor<'info> {
-    pub authority: Signer<'info>,
-    #[account(
-        mut,
-        has_one = authority @ DaoError::Unauthorized,
-        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
-        bump = dao.bump
-    )]
-    pub dao: Account<'info, Dao>,
-    /// CHECK: Verified via metadata off-chain / CPI
-    pub sponsor_vault: UncheckedAccount<'info>,
-}
+            let burn_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.pass_mint.to_account_info(),
+                    from: ctx.accounts.old_pass_account.to_account_info(),
+                    authority: old_wallet.to_account_info(),
+                },
+            );
+            token::burn(burn_ctx, passes)?;
 
-#[derive(Accounts)]
-pub struct RecordRelaySpend<'info> {
-    #[account(
-        mut,
-        has_one = authority @ DaoError::Unauthorized,
-        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
-        bump = dao.bump
-    )]
-    pub dao: Account<'info, Dao>,
-    pub authority: Signer<'info>,
-}
+            let close_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.old_pass_account.to_account_info(),
+                    destination: old_wallet.to_account_info(),
+                    authority: old_wallet.to_account_info(),
+                },
+            );
+            token::close_account(close_ctx)?;
+        } else if !frozen {
+            // Passes issued before they were soulbound must not move on either
+            freeze_pass(
+                &ctx.accounts.token_program,
+                &ctx.accounts.old_pass_account,
+                &ctx.accounts.pass_mint,
+                &ctx.accounts.dao.to_account_info(),
+                signer_seeds,
+            )?;
+        }
 
-#[account]
-pub struct Dao {
-    pub authority: Pubkey,
-    pub pass_mint: Pubkey,
-    pub sponsor_mint: Pubkey,
-    pub sponsor_vault: Pubkey,
-    pub max_relay_spend: u64,
-    pub relay_spent: u64,
-    pub relay_epoch: u64,
-    pub total_members: u32,
-    pub bump: u8,
-    pub name_seed: [u8; 8],
-    pub reserved: [u8; 7],
-}
+        let mint_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.pass_mint.to_account_info(),
+                to: ctx.accounts.new_pass_account.to_account_info(),
+                authority: ctx.accounts.dao.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(mint_ctx, passes)?;
 
-impl Dao {
-    pub const LEN: usize = 8  // discriminator
-        + 32  // authority
-        + 32  // pass_mint
-        + 32  // sponsor_mint
-        + 32  // sponsor_vault
-        + 8   // max_relay_spend
-        + 8   // relay_spent
-        + 8   // relay_epoch
-        + 4   // total_members
-        + 1   // bump
-        + 8   // name_seed
-        + 7;  // reserved
-    pub const SEED_PREFIX: &'static [u8] = b"dao";
+        freeze_pass(
+            &ctx.accounts.token_program,
+            &ctx.accounts.new_pass_account,
+            &ctx.accounts.pass_mint,
+            &ctx.accounts.dao.to_account_info(),
+            signer_seeds,
+        )?;
 
-}
+        let mut migrated = (**old_member).clone();
+        migrated.owner = ctx.accounts.new_wallet.key();
+        migrated.member_pass_account = ctx.accounts.new_pass_account.key();
+        migrated.version = Member::VERSION;
+        ctx.accounts.new_member.set_inner(migrated);
 
-#[account]
-pub struct Member {
-    pub dao: Pubkey,
-    pub owner: Pubkey,
-    pub membership_mint: Pubkey,
-    pub member_pass_account: Pubkey,
-    pub joined_ts: i64,
-    pub mint_count: u64,
-    pub reserved: [u8; 16],
-}
+        let rent_destination = match ctx.accounts.old_wallet.as_ref() {
+            Some(old_wallet) => old_wallet.to_account_info(),
+            None => ctx.accounts.authority.to_account_info(),
+        };
+        ctx.accounts.member.close(rent_destination)?;
 
-impl Member {
-    pub const LEN: usize = 8 // discriminator
-        + 32 // dao
-        + 32 // owner
-        + 32 // membership_mint
-        + 32 // pass account
-        + 8  // joined_ts
-        + 8  // mint_count
-        + 16; // reserved
-    pub const SEED_PREFIX: &'static [u8] = b"member";
-}
+        Ok(())
+    }
 
-#[error_code]
-pub enum DaoError {
-    #[msg("Unauthorized")]
-    Unauthorized,
-    #[msg("Invalid limit")]
-    InvalidLimit,
-    #[msg("Invalid spend amount")]
-    InvalidSpend,
-    #[msg("Relay budget exceeded")]
-    RelayBudgetExceeded,
-    #[msg("Mint mismatch")]
-    MintMismatch,
-    #[msg("Math overflow")]
-    MathOverflow,
-    #[msg("Bump missing")]
-    BumpNotFound,
+    /// Sets the term and price of a membership; a zero duration means memberships never expire.
+    pub fn set_membership_terms(
+        ctx: Context<SetMembershipTerms>,
+        membership_duration_secs: i64,
+        dues_amount: u64,
+    ) -> Result<()> {
+        require!(membership_duration_secs >= 0, DaoError::InvalidDuration);
+
+        let dao = &mut ctx.accounts.dao;
+        dao.membership_duration_secs = membership_duration_secs;
+        dao.dues_amount = dues_amount;
+
+        Ok(())
+    }
+
+    /// Collects one term of dues into the DAO treasury and extends the member's expiry
+    /// from whichever is later: now or the current expiry.
+    pub fn renew_membership(ctx: Context<RenewMembership>) -> Result<()> {
+        let dao = &ctx.accounts.dao;
+        require!(dao.membership_duration_secs > 0, DaoError::MembershipNotExpiring);
+        // Members who joined before terms were set hold lifetime passes
+        require!(ctx.accounts.member.expires_ts != 0, DaoError::LifetimeMembership);
+        require!(!ctx.accounts.member.revoked, DaoError::MemberRevoked);
+
+        if dao.dues_amount > 0 {
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer_token_account.to_account_info(),
+                    to: ctx.accounts.dues_treasury.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            );
+            token::transfer(transfer_ctx, dao.dues_amount)?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let member = &mut ctx.accounts.member;
+        member.expires_ts = dao.expiry_from(member.expires_ts.max(now))?;
+
+        Ok(())
+    }
+
+    /// Caps how much relay spend each member may draw per epoch; zero removes the cap.
+    pub fn set_member_relay_cap(ctx: Context<SetMemberRelayCap>, max_member_relay_spend: u64) -> Result<()> {
+        ctx.accounts.dao.max_member_relay_spend = max_member_relay_spend;
+
+        Ok(())
+    }
+
+    /// Sets the DAO relay cap and budget window. Switching window length or mode
+    /// leaves current spend in place; it is measured against the new window from
+    /// the next charge on.
+    pub fn configure_sponsor(
+        ctx: Context<ConfigureSponsor>,
+        new_cap: u64,
+        relay_window_secs: u64,
+        relay_window: RelayWindow,
+    ) -> Result<()> {
+        require!(new_cap > 0, DaoError::InvalidLimit);
+        require!(relay_window_secs > 0, DaoError::InvalidDuration);
+
+        let dao = &mut ctx.accounts.dao;
+        dao.max_relay_spend = new_cap;
+        dao.relay_window_secs = relay_window_secs;
+        dao.relay_window = relay_window;
+        dao.sponsor_vault = ctx.accounts.sponsor_vault.key();
+
+        Ok(())
+    }
+
+    pub fn grant_role(ctx: Context<UpdateMemberRoles>, roles: u8) -> Result<()> {
+        require!(roles != 0 && roles & !ROLE_ALL == 0, DaoError::InvalidRoles);
+        let member = &mut ctx.accounts.member;
+        require!(!member.revoked, DaoError::MemberRevoked);
+
+        member.roles |= roles;
+
+        Ok(())
+    }
+
+    /// Drops roles from a member; `ROLE_MEMBER` itself is only removed by `revoke_pass`.
+    pub fn revoke_role(ctx: Context<UpdateMemberRoles>, roles: u8) -> Result<()> {
+        require!(
+            roles != 0 && roles & !ROLE_ALL == 0 && roles & ROLE_MEMBER == 0,
+            DaoError::InvalidRoles
+        );
+
+        let member = &mut ctx.accounts.member;
+        member.roles &= !roles;
+
+        Ok(())
+    }
+
+    /// Sets the relay budget shared by all members relaying under `role`, a single `ROLE_*` bit.
+    /// A zero cap stops budgeting the role.
+    pub fn set_role_budget(ctx: Context<SetRoleBudget>, role: u8, max_relay_spend: u64) -> Result<()> {
+        let role_budget = &mut ctx.accounts.role_budget;
+        if role_budget.dao == Pubkey::default() {
+            role_budget.dao = ctx.accounts.dao.key();
+            role_budget.role = role;
+            role_budget.relay_epoch = relay_now(&Clock::get()?);
+            role_budget.bump = ctx.bumps.role_budget;
+        }
+        role_budget.max_relay_spend = max_relay_spend;
+        if max_relay_spend == 0 {
+            ctx.accounts.dao.budgeted_roles &= !role;
+        } else {
+            ctx.accounts.dao.budgeted_roles |= role;
+        }
+
+        Ok(())
+    }
+
+    /// Registers a relayer key, or updates its limit and expiry, so the relayer
+    /// service can sign spend records without holding the authority key.
+    pub fn add_relayer(
+        ctx: Context<AddRelayer>,
+        relayer_key: Pubkey,
+        max_relay_spend: u64,
+        expires_ts: i64,
+    ) -> Result<()> {
+        require!(max_relay_spend > 0, DaoError::InvalidLimit);
+        let clock = Clock::get()?;
+        require!(
+            expires_ts == 0 || expires_ts > clock.unix_timestamp,
+            DaoError::InvalidDuration
+        );
+
+        let registry = &mut ctx.accounts.relayer;
+        if registry.dao == Pubkey::default() {
+            registry.dao = ctx.accounts.dao.key();
+            registry.relayer = relayer_key;
+            registry.relay_epoch = relay_now(&clock);
+            registry.bump = ctx.bumps.relayer;
+        }
+        registry.max_relay_spend = max_relay_spend;
+        registry.expires_ts = expires_ts;
+
+        Ok(())
+    }
+
+    /// Deregisters a relayer key and returns its rent to the authority.
+    pub fn remove_relayer(_ctx: Context<RemoveRelayer>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Records sponsored relay spend against the DAO budget and, when a member and
+    /// role budget are given, against that role's budget as well. Signed by the
+    /// authority or by a registered relayer, which is charged against its own limit.
+    ///
+    /// Each call leaves a receipt keyed by `message_hash` (the sponsored
+    /// transaction's message hash, or a hash of its signature), so the same relay
+    /// can't be recorded twice and sponsor outflows can be reconciled.
+    pub fn record_relay_spend(
+        ctx: Context<RecordRelaySpend>,
+        spend: u64,
+        message_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(spend > 0, DaoError::InvalidSpend);
+        let clock = Clock::get()?;
+
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.dao = ctx.accounts.dao.key();
+        receipt.member = ctx.accounts.member.owner;
+        receipt.relayer = ctx.accounts.signer.key();
+        receipt.message_hash = message_hash;
+        receipt.spend = spend;
+        receipt.slot = clock.slot;
+        receipt.recorded_ts = clock.unix_timestamp;
+        receipt.reimbursed = false;
+        receipt.bump = ctx.bumps.receipt;
+
+        authorize_relay_signer(
+            &ctx.accounts.dao,
+            &ctx.accounts.signer.key(),
+            ctx.accounts.relayer.as_deref_mut(),
+            spend,
+            &clock,
+        )?;
+
+        charge_relay_spend(
+            &mut ctx.accounts.dao,
+            &mut ctx.accounts.member,
+            ctx.accounts.role_budget.as_deref_mut(),
+            spend,
+            &clock,
+        )
+    }
+
+    /// Returns a relay receipt's rent to whoever paid for it once the audit
+    /// retention period has passed.
+    pub fn close_relay_receipt(ctx: Context<CloseRelayReceipt>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= ctx.accounts.receipt.recorded_ts.saturating_add(RELAY_RECEIPT_RETENTION_SECS),
+            DaoError::ReceiptStillNeeded
+        );
+
+        Ok(())
+    }
+
+    /// Tops up the DAO's sponsor vault; anyone may fund it.
+    pub fn fund_sponsor_vault(ctx: Context<FundSponsorVault>, amount: u64) -> Result<()> {
+        require!(amount > 0, DaoError::InvalidAmount);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.sponsor_vault.to_account_info(),
+                authority: ctx.accounts.funder.to_account_info(),
+            },
+        );
+        token::transfer(transfer_ctx, amount)?;
+
+        Ok(())
+    }
+
+    pub fn withdraw_sponsor_funds(ctx: Context<WithdrawSponsorFunds>, amount: u64) -> Result<()> {
+        require!(amount > 0, DaoError::InvalidAmount);
+
+        let dao = &ctx.accounts.dao;
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Dao::SEED_PREFIX,
+                dao.authority.as_ref(),
+                &dao.name_seed,
+                &[dao.bump],
+            ]
+        ];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.sponsor_vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.dao.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, amount)?;
+
+        Ok(())
+    }
+
+    /// Pays a relayer out of the sponsor vault for spend it already recorded. The
+    /// budgets were charged by `record_relay_spend`, so each receipt pays out once.
+    // `message_hash` is only used by the receipt's seeds
+    #[allow(unused_variables)]
+    pub fn reimburse_relayer(ctx: Context<ReimburseRelayer>, message_hash: [u8; 32]) -> Result<()> {
+        let signer = ctx.accounts.signer.key();
+        let receipt = &ctx.accounts.receipt;
+        require!(
+            signer == ctx.accounts.dao.authority || signer == receipt.relayer,
+            DaoError::Unauthorized
+        );
+        let amount = receipt.spend;
+
+        let dao = &ctx.accounts.dao;
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Dao::SEED_PREFIX,
+                dao.authority.as_ref(),
+                &dao.name_seed,
+                &[dao.bump],
+            ]
+        ];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.sponsor_vault.to_account_info(),
+                to: ctx.accounts.relayer_token_account.to_account_info(),
+                authority: ctx.accounts.dao.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, amount)?;
+
+        ctx.accounts.receipt.reimbursed = true;
+
+        Ok(())
+    }
+}
+
+/// Relay budget window for DAOs created before it was configurable; 432,000
+/// slots at ~0.4s each lasted about two days.
+pub const DEFAULT_RELAY_WINDOW_SECS: u64 = 2 * 24 * 60 * 60;
+
+// Member roles, stored as a bitmask on `Member.roles`
+pub const ROLE_MEMBER: u8 = 1 << 0;
+pub const ROLE_CONTRIBUTOR: u8 = 1 << 1;
+pub const ROLE_COUNCIL: u8 = 1 << 2;
+pub const ROLE_ADMIN: u8 = 1 << 3;
+pub const ROLE_ALL: u8 = ROLE_MEMBER | ROLE_CONTRIBUTOR | ROLE_COUNCIL | ROLE_ADMIN;
+
+pub const SPONSOR_VAULT_SEED_PREFIX: &[u8] = b"sponsor_vault";
+
+/// How long relay receipts must stay on-chain for auditors before their rent
+/// can be reclaimed.
+pub const RELAY_RECEIPT_RETENTION_SECS: i64 = 90 * 24 * 60 * 60;
+
+/// Charges relay spend made for `member` to the DAO budget, the member's own
+/// quota and the budget of the member's most senior budgeted role, which has to
+/// be given whenever the member holds a budgeted role.
+fn charge_relay_spend(
+    dao: &mut Dao,
+    member: &mut Member,
+    role_budget: Option<&mut RoleBudget>,
+    spend: u64,
+    clock: &Clock,
+) -> Result<()> {
+    require!(member.is_active(clock.unix_timestamp), DaoError::MemberInactive);
+    let window = dao.budget_window(clock);
+
+    charge_relay_budget(
+        &mut dao.relay_spent,
+        &mut dao.relay_epoch,
+        &mut dao.relay_prev_spent,
+        &mut dao.relay_prev_ts,
+        dao.max_relay_spend,
+        spend,
+        window,
+        DaoError::RelayBudgetExceeded,
+    )?;
+
+    // Spend is tracked even when uncapped so a cap set later starts from real usage
+    let member_cap = if dao.max_member_relay_spend == 0 {
+        u64::MAX
+    } else {
+        dao.max_member_relay_spend
+    };
+    charge_relay_budget(
+        &mut member.relay_spent,
+        &mut member.relay_epoch,
+        &mut member.relay_prev_spent,
+        &mut member.relay_prev_ts,
+        member_cap,
+        spend,
+        window,
+        DaoError::MemberRelayQuotaExceeded,
+    )?;
+
+    let budgeted = member.role_mask() & dao.budgeted_roles;
+    if budgeted == 0 {
+        return Ok(());
+    }
+    // Pinning the budget to the most senior role stops members holding several
+    // roles from charging whichever budget is loosest
+    let role_budget = role_budget.ok_or(DaoError::RoleBudgetRequired)?;
+    require!(member.has_any_role(role_budget.role), DaoError::MissingRole);
+    let senior_role = 1 << (u8::BITS - 1 - budgeted.leading_zeros());
+    require!(role_budget.role == senior_role, DaoError::RoleBudgetRequired);
+    charge_relay_budget(
+        &mut role_budget.relay_spent,
+        &mut role_budget.relay_epoch,
+        &mut role_budget.relay_prev_spent,
+        &mut role_budget.relay_prev_ts,
+        role_budget.max_relay_spend,
+        spend,
+        window,
+        DaoError::RelayBudgetExceeded,
+    )
+}
+
+/// Lets the DAO authority relay freely; any other signer must be a registered,
+/// unexpired relayer with room left under its own limit.
+fn authorize_relay_signer(
+    dao: &Dao,
+    signer: &Pubkey,
+    relayer: Option<&mut Relayer>,
+    spend: u64,
+    clock: &Clock,
+) -> Result<()> {
+    let Some(relayer) = relayer else {
+        require_keys_eq!(*signer, dao.authority, DaoError::Unauthorized);
+        return Ok(());
+    };
+
+    require_keys_eq!(*signer, relayer.relayer, DaoError::Unauthorized);
+    require!(relayer.is_active(clock.unix_timestamp), DaoError::RelayerExpired);
+
+    charge_relay_budget(
+        &mut relayer.relay_spent,
+        &mut relayer.relay_epoch,
+        &mut relayer.relay_prev_spent,
+        &mut relayer.relay_prev_ts,
+        relayer.max_relay_spend,
+        spend,
+        dao.budget_window(clock),
+        DaoError::RelayerLimitExceeded,
+    )
+}
+
+/// Adds `spend` to a relay budget after rolling its window forward to `window.now`.
+///
+/// A fixed window forgets all spend once it has lapsed. A rolling window keeps
+/// the current bucket, last charged at `epoch`, and an earlier one last charged at
+/// `prev_ts`. A bucket counts in full until its last charge leaves the trailing
+/// window, so no window of `window.secs` ever holds more than `max_spend`.
+#[allow(clippy::too_many_arguments)]
+fn charge_relay_budget(
+    spent: &mut u64,
+    epoch: &mut u64,
+    prev_spent: &mut u64,
+    prev_ts: &mut u64,
+    max_spend: u64,
+    spend: u64,
+    window: BudgetWindow,
+    exceeded: DaoError,
+) -> Result<()> {
+    let lapsed = |ts: u64| window.now.saturating_sub(ts) >= window.secs;
+    match window.mode {
+        RelayWindow::Fixed => {
+            if lapsed(*epoch) {
+                *epoch = window.now;
+                *spent = 0;
+            }
+            *prev_spent = 0;
+        }
+        RelayWindow::Rolling => {
+            if lapsed(*epoch) {
+                *spent = 0;
+            }
+            // Start a new bucket once the earlier one has left the window
+            if lapsed(*prev_ts) {
+                *prev_spent = *spent;
+                *prev_ts = *epoch;
+                *spent = 0;
+            }
+            *epoch = window.now;
+        }
+    }
+
+    *spent = spent.checked_add(spend).ok_or(DaoError::MathOverflow)?;
+    if spent.saturating_add(*prev_spent) > max_spend {
+        return Err(exceeded.into());
+    }
+
+    Ok(())
+}
+
+/// Relay budgets are timed by the cluster clock, in whole seconds.
+fn relay_now(clock: &Clock) -> u64 {
+    u64::try_from(clock.unix_timestamp).unwrap_or_default()
+}
+
+/// A DAO's relay window settings, evaluated at one point in time.
+#[derive(Clone, Copy)]
+struct BudgetWindow {
+    now: u64,
+    secs: u64,
+    mode: RelayWindow,
+}
+
+/// Checks a keccak Merkle proof built with sorted pairs, so proofs carry no
+/// left/right flags.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == root
+}
+
+fn freeze_pass<'info>(
+    token_program: &Program<'info, Token>,
+    pass_account: &Account<'info, TokenAccount>,
+    pass_mint: &Account<'info, Mint>,
+    dao: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let freeze_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        FreezeAccount {
+            account: pass_account.to_account_info(),
+            mint: pass_mint.to_account_info(),
+            authority: dao.clone(),
+        },
+        signer_seeds,
+    );
+    token::freeze_account(freeze_ctx)
+}
+
+fn thaw_pass<'info>(
+    token_program: &Program<'info, Token>,
+    pass_account: &Account<'info, TokenAccount>,
+    pass_mint: &Account<'info, Mint>,
+    dao: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let thaw_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        ThawAccount {
+            account: pass_account.to_account_info(),
+            mint: pass_mint.to_account_info(),
+            authority: dao.clone(),
+        },
+        signer_seeds,
+    );
+    token::thaw_account(thaw_ctx)
+}
+
+// Instruction families the guardian can pause independently via `Config.paused`
+pub const PAUSE_DAOS: u16 = 1 << 0;
+pub const PAUSE_MEMBERSHIP: u16 = 1 << 1;
+pub const PAUSE_RELAY: u16 = 1 << 2;
+pub const PAUSE_ALL: u16 = PAUSE_DAOS | PAUSE_MEMBERSHIP | PAUSE_RELAY;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = Config::LEN,
+        seeds = [Config::SEED_PREFIX],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, DaoPass>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ DaoError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ DaoError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        has_one = guardian @ DaoError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateDao<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Legacy layout; owner checked here, discriminator and size in the handler
+    #[account(mut, owner = crate::ID @ DaoError::Unauthorized)]
+    pub dao: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMember<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Legacy layout; owner checked here, discriminator and size in the handler
+    #[account(mut, owner = crate::ID @ DaoError::Unauthorized)]
+    pub member: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name_seed: [u8; 8], sponsor_mint: Pubkey)]
+pub struct InitializeDao<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = Dao::LEN,
+        seeds = [Dao::SEED_PREFIX, authority.key().as_ref(), &name_seed],
+        bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 0,
+        mint::authority = dao,
+        mint::freeze_authority = dao
+    )]
+    pub pass_mint: Account<'info, Mint>,
+    #[account(constraint = sponsor_mint_account.key() == sponsor_mint @ DaoError::MintMismatch)]
+    pub sponsor_mint_account: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [SPONSOR_VAULT_SEED_PREFIX, dao.key().as_ref()],
+        bump,
+        token::mint = sponsor_mint_account,
+        token::authority = dao
+    )]
+    pub sponsor_vault: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct IssuePass<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub member_wallet: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(mut)]
+    pub pass_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = member_wallet,
+        space = Member::LEN,
+        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), member_wallet.key().as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    #[account(
+        init_if_needed,
+        payer = member_wallet,
+        associated_token::mint = pass_mint,
+        associated_token::authority = member_wallet
+    )]
+    pub member_pass_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_MEMBERSHIP) @ DaoError::MembershipPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetJoinPolicy<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct RequestJoin<'info> {
+    #[account(mut)]
+    pub applicant: Signer<'info>,
+    #[account(
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        init,
+        payer = applicant,
+        space = JoinRequest::LEN,
+        seeds = [JoinRequest::SEED_PREFIX, dao.key().as_ref(), applicant.key().as_ref()],
+        bump
+    )]
+    pub join_request: Account<'info, JoinRequest>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_MEMBERSHIP) @ DaoError::MembershipPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReviewJoinRequests<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_MEMBERSHIP) @ DaoError::MembershipPaused
+    )]
+    pub config: Account<'info, Config>,
+    // remaining_accounts: (join_request, applicant wallet) pairs, one per decision
+}
+
+#[derive(Accounts)]
+pub struct JoinDao<'info> {
+    #[account(mut)]
+    pub applicant: Signer<'info>,
+    #[account(
+        mut,
+        has_one = pass_mint @ DaoError::MintMismatch,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(mut)]
+    pub pass_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = applicant,
+        space = Member::LEN,
+        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), applicant.key().as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    #[account(
+        init_if_needed,
+        payer = applicant,
+        associated_token::mint = pass_mint,
+        associated_token::authority = applicant
+    )]
+    pub member_pass_account: Account<'info, TokenAccount>,
+    /// Required under the `Application` policy; closed once the member is enrolled
+    #[account(
+        mut,
+        seeds = [JoinRequest::SEED_PREFIX, dao.key().as_ref(), applicant.key().as_ref()],
+        bump = join_request.bump
+    )]
+    pub join_request: Option<Account<'info, JoinRequest>>,
+    /// Required when the DAO charges dues
+    #[account(
+        mut,
+        constraint = payer_token_account.owner == applicant.key() @ DaoError::Unauthorized
+    )]
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(address = dao.sponsor_mint @ DaoError::MintMismatch)]
+    pub sponsor_mint: Option<Account<'info, Mint>>,
+    /// Created by the first paid join
+    #[account(
+        init_if_needed,
+        payer = applicant,
+        associated_token::mint = sponsor_mint,
+        associated_token::authority = dao
+    )]
+    pub dues_treasury: Option<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_MEMBERSHIP) @ DaoError::MembershipPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct RevokePass<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ DaoError::Unauthorized,
+        has_one = pass_mint @ DaoError::MintMismatch,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        has_one = dao @ DaoError::Unauthorized,
+        has_one = member_pass_account @ DaoError::PassAccountMismatch,
+        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), member.owner.as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    pub pass_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub member_pass_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_MEMBERSHIP) @ DaoError::MembershipPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMemberWallet<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ DaoError::Unauthorized,
+        has_one = pass_mint @ DaoError::MintMismatch,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(mut)]
+    pub pass_mint: Account<'info, Mint>,
+    /// Optional co-signer that burns its passes and takes back the rent; the
+    /// authority gets the member rent when it is left out
+    #[account(mut, address = member.owner @ DaoError::Unauthorized)]
+    pub old_wallet: Option<Signer<'info>>,
+    #[account(
+        mut,
+        has_one = dao @ DaoError::Unauthorized,
+        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), member.owner.as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    #[account(mut, address = member.member_pass_account @ DaoError::PassAccountMismatch)]
+    pub old_pass_account: Account<'info, TokenAccount>,
+    /// CHECK: Any wallet the authority moves the membership to
+    pub new_wallet: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = Member::LEN,
+        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), new_wallet.key().as_ref()],
+        bump
+    )]
+    pub new_member: Account<'info, Member>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = pass_mint,
+        associated_token::authority = new_wallet
+    )]
+    pub new_pass_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_MEMBERSHIP) @ DaoError::MembershipPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetMembershipTerms<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct RenewMembership<'info> {
+    /// Pays the dues; need not be the member, so memberships can be gifted
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        has_one = sponsor_mint @ DaoError::MintMismatch,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        has_one = dao @ DaoError::Unauthorized,
+        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), member.owner.as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    pub sponsor_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = payer_token_account.owner == payer.key() @ DaoError::Unauthorized,
+        constraint = payer_token_account.mint == sponsor_mint.key() @ DaoError::MintMismatch
+    )]
+    pub payer_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = sponsor_mint,
+        associated_token::authority = dao
+    )]
+    pub dues_treasury: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_MEMBERSHIP) @ DaoError::MembershipPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureSponsor<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ DaoError::Unauthorized,
+        has_one = sponsor_mint @ DaoError::MintMismatch,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    pub sponsor_mint: Account<'info, Mint>,
+    /// Created here for DAOs that predate program-held sponsor funds
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [SPONSOR_VAULT_SEED_PREFIX, dao.key().as_ref()],
+        bump,
+        token::mint = sponsor_mint,
+        token::authority = dao
+    )]
+    pub sponsor_vault: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetMemberRelayCap<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct FundSponsorVault<'info> {
+    pub funder: Signer<'info>,
+    #[account(
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        constraint = funder_token_account.owner == funder.key() @ DaoError::Unauthorized,
+        constraint = funder_token_account.mint == dao.sponsor_mint @ DaoError::MintMismatch
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = dao.sponsor_vault @ DaoError::SponsorVaultMismatch,
+        seeds = [SPONSOR_VAULT_SEED_PREFIX, dao.key().as_ref()],
+        bump
+    )]
+    pub sponsor_vault: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSponsorFunds<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        address = dao.sponsor_vault @ DaoError::SponsorVaultMismatch,
+        seeds = [SPONSOR_VAULT_SEED_PREFIX, dao.key().as_ref()],
+        bump
+    )]
+    pub sponsor_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = destination.mint == dao.sponsor_mint @ DaoError::MintMismatch
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_hash: [u8; 32])]
+pub struct ReimburseRelayer<'info> {
+    /// The DAO authority or the relayer that recorded the spend
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        has_one = dao @ DaoError::Unauthorized,
+        seeds = [RelayReceipt::SEED_PREFIX, dao.key().as_ref(), &message_hash],
+        bump = receipt.bump,
+        constraint = !receipt.reimbursed @ DaoError::AlreadyReimbursed
+    )]
+    pub receipt: Account<'info, RelayReceipt>,
+    #[account(
+        mut,
+        address = dao.sponsor_vault @ DaoError::SponsorVaultMismatch,
+        seeds = [SPONSOR_VAULT_SEED_PREFIX, dao.key().as_ref()],
+        bump
+    )]
+    pub sponsor_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = relayer_token_account.mint == dao.sponsor_mint @ DaoError::MintMismatch,
+        constraint = relayer_token_account.owner == receipt.relayer @ DaoError::RelayerAccountMismatch
+    )]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_RELAY) @ DaoError::RelayPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMemberRoles<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        has_one = dao @ DaoError::Unauthorized,
+        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), member.owner.as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_MEMBERSHIP) @ DaoError::MembershipPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: u8)]
+pub struct SetRoleBudget<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RoleBudget::LEN,
+        seeds = [RoleBudget::SEED_PREFIX, dao.key().as_ref(), &[role]],
+        bump,
+        constraint = role.count_ones() == 1 && role & !ROLE_ALL == 0 @ DaoError::InvalidRoles
+    )]
+    pub role_budget: Account<'info, RoleBudget>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer_key: Pubkey)]
+pub struct AddRelayer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = Relayer::LEN,
+        seeds = [Relayer::SEED_PREFIX, dao.key().as_ref(), relayer_key.as_ref()],
+        bump
+    )]
+    pub relayer: Account<'info, Relayer>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveRelayer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ DaoError::Unauthorized,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = dao @ DaoError::Unauthorized,
+        seeds = [Relayer::SEED_PREFIX, dao.key().as_ref(), relayer.relayer.as_ref()],
+        bump = relayer.bump
+    )]
+    pub relayer: Account<'info, Relayer>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DAOS) @ DaoError::DaosPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+#[instruction(spend: u64, message_hash: [u8; 32])]
+pub struct RecordRelaySpend<'info> {
+    #[account(
+        mut,
+        seeds = [Dao::SEED_PREFIX, dao.authority.as_ref(), &dao.name_seed],
+        bump = dao.bump
+    )]
+    pub dao: Account<'info, Dao>,
+    /// The DAO authority or a registered relayer; pays for the receipt
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// Registry entry of the signing relayer; omitted when the authority signs
+    #[account(
+        mut,
+        has_one = dao @ DaoError::Unauthorized,
+        seeds = [Relayer::SEED_PREFIX, dao.key().as_ref(), signer.key().as_ref()],
+        bump = relayer.bump
+    )]
+    pub relayer: Option<Account<'info, Relayer>>,
+    /// The member being relayed for; charged against their own quota
+    #[account(
+        mut,
+        has_one = dao @ DaoError::Unauthorized,
+        seeds = [Member::SEED_PREFIX, dao.key().as_ref(), member.owner.as_ref()],
+        bump
+    )]
+    pub member: Account<'info, Member>,
+    /// Budget of one of the member's roles, when relaying under that role
+    #[account(
+        mut,
+        has_one = dao @ DaoError::Unauthorized,
+        seeds = [RoleBudget::SEED_PREFIX, dao.key().as_ref(), &[role_budget.role]],
+        bump = role_budget.bump
+    )]
+    pub role_budget: Option<Account<'info, RoleBudget>>,
+    #[account(
+        init,
+        payer = signer,
+        space = RelayReceipt::LEN,
+        seeds = [RelayReceipt::SEED_PREFIX, dao.key().as_ref(), &message_hash],
+        bump
+    )]
+    pub receipt: Account<'info, RelayReceipt>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_RELAY) @ DaoError::RelayPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRelayReceipt<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    #[account(
+        mut,
+        close = relayer,
+        has_one = relayer @ DaoError::Unauthorized,
+        seeds = [RelayReceipt::SEED_PREFIX, receipt.dao.as_ref(), &receipt.message_hash],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, RelayReceipt>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_RELAY) @ DaoError::RelayPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[account]
+#[derive(Default)]
+pub struct Dao {
+    pub authority: Pubkey,
+    pub pass_mint: Pubkey,
+    pub sponsor_mint: Pubkey,
+    pub sponsor_vault: Pubkey,
+    pub max_relay_spend: u64,
+    pub relay_spent: u64,
+    /// Unix timestamp the relay window started (fixed) or was last charged (rolling).
+    pub relay_epoch: u64,
+    pub total_members: u32,
+    pub bump: u8,
+    pub name_seed: [u8; 8],
+    /// `ROLE_*` bits that have a `RoleBudget`; relays for members holding one must charge it.
+    pub budgeted_roles: u8,
+    pub reserved: [u8; 6],
+    pub version: u8,
+    /// Length of one membership term; zero means memberships never expire.
+    pub membership_duration_secs: i64,
+    /// Dues per term, in `sponsor_mint`.
+    pub dues_amount: u64,
+    pub join_policy: JoinPolicy,
+    /// keccak Merkle root of allowlisted wallets, used by `JoinPolicy::Allowlist`.
+    pub allowlist_root: [u8; 32],
+    /// Per-member relay spend allowed each epoch; zero leaves only the DAO-wide cap.
+    pub max_member_relay_spend: u64,
+    /// Length of the relay budget window shared by the DAO, member, role and
+    /// relayer budgets.
+    pub relay_window_secs: u64,
+    pub relay_window: RelayWindow,
+    /// Earlier rolling-window spend, counted until `relay_prev_ts` leaves the window.
+    pub relay_prev_spent: u64,
+    /// Unix timestamp of the last charge included in `relay_prev_spent`.
+    pub relay_prev_ts: u64,
+}
+
+impl Dao {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // authority
+        + 32  // pass_mint
+        + 32  // sponsor_mint
+        + 32  // sponsor_vault
+        + 8   // max_relay_spend
+        + 8   // relay_spent
+        + 8   // relay_epoch
+        + 4   // total_members
+        + 1   // bump
+        + 8   // name_seed
+        + 1   // budgeted_roles
+        + 6   // reserved
+        + 1   // version
+        + 8   // membership_duration_secs
+        + 8   // dues_amount
+        + 1   // join_policy
+        + 32  // allowlist_root
+        + 8   // max_member_relay_spend
+        + 8   // relay_window_secs
+        + 1   // relay_window
+        + 8   // relay_prev_spent
+        + 8;  // relay_prev_ts
+    pub const SEED_PREFIX: &'static [u8] = b"dao";
+    /// Layout written by `initialize_dao`; v1 had no version byte, v2 adds it along
+    /// with membership terms, the join policy, the per-member relay cap and the
+    /// configurable relay window.
+    pub const VERSION: u8 = 2;
+
+    fn budget_window(&self, clock: &Clock) -> BudgetWindow {
+        BudgetWindow {
+            now: relay_now(clock),
+            secs: self.relay_window_secs,
+            mode: self.relay_window,
+        }
+    }
+
+    /// Expiry of a term starting at `start_ts`, or zero when memberships never expire.
+    pub fn expiry_from(&self, start_ts: i64) -> Result<i64> {
+        if self.membership_duration_secs == 0 {
+            return Ok(0);
+        }
+        Ok(start_ts
+            .checked_add(self.membership_duration_secs)
+            .ok_or(DaoError::MathOverflow)?)
+    }
+}
+
+/// `Dao` as laid out before accounts carried a version byte. Its `relay_epoch`
+/// holds a slot, which reads as a long-lapsed timestamp, so the first charge
+/// after migrating starts a fresh window.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DaoV1 {
+    pub authority: Pubkey,
+    pub pass_mint: Pubkey,
+    pub sponsor_mint: Pubkey,
+    pub sponsor_vault: Pubkey,
+    pub max_relay_spend: u64,
+    pub relay_spent: u64,
+    pub relay_epoch: u64,
+    pub total_members: u32,
+    pub bump: u8,
+    pub name_seed: [u8; 8],
+    pub reserved: [u8; 7],
+}
+
+impl DaoV1 {
+    pub const LEN: usize = 8  // discriminator
+        + 32  // authority
+        + 32  // pass_mint
+        + 32  // sponsor_mint
+        + 32  // sponsor_vault
+        + 8   // max_relay_spend
+        + 8   // relay_spent
+        + 8   // relay_epoch
+        + 4   // total_members
+        + 1   // bump
+        + 8   // name_seed
+        + 7;  // reserved
+
+    /// Parses raw v1 account data, discriminator included.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 && data[..8] == Dao::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    pub fn migrate(self) -> Dao {
+        Dao {
+            authority: self.authority,
+            pass_mint: self.pass_mint,
+            sponsor_mint: self.sponsor_mint,
+            sponsor_vault: self.sponsor_vault,
+            max_relay_spend: self.max_relay_spend,
+            relay_spent: self.relay_spent,
+            relay_epoch: self.relay_epoch,
+            total_members: self.total_members,
+            bump: self.bump,
+            name_seed: self.name_seed,
+            version: Dao::VERSION,
+            relay_window_secs: DEFAULT_RELAY_WINDOW_SECS,
+            ..Default::default()
+        }
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct Member {
+    pub dao: Pubkey,
+    pub owner: Pubkey,
+    pub membership_mint: Pubkey,
+    pub member_pass_account: Pubkey,
+    pub joined_ts: i64,
+    pub mint_count: u64,
+    /// End of the paid term; zero means the membership never expires.
+    pub expires_ts: i64,
+    /// `ROLE_*` bits held on top of the implicit `ROLE_MEMBER`.
+    pub roles: u8,
+    pub reserved: [u8; 7],
+    pub version: u8,
+    pub revoked: bool,
+    pub revoked_ts: i64,
+    pub revoke_reason_hash: [u8; 32],
+    pub relay_spent: u64,
+    /// Timed by the DAO's relay window, like `Dao.relay_epoch`.
+    pub relay_epoch: u64,
+    pub relay_prev_spent: u64,
+    pub relay_prev_ts: u64,
+}
+
+impl Member {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // dao
+        + 32 // owner
+        + 32 // membership_mint
+        + 32 // pass account
+        + 8  // joined_ts
+        + 8  // mint_count
+        + 8  // expires_ts
+        + 1  // roles
+        + 7  // reserved
+        + 1  // version
+        + 1  // revoked
+        + 8  // revoked_ts
+        + 32 // revoke_reason_hash
+        + 8  // relay_spent
+        + 8  // relay_epoch
+        + 8  // relay_prev_spent
+        + 8; // relay_prev_ts
+    pub const SEED_PREFIX: &'static [u8] = b"member";
+    /// Layout written by `issue_pass`; v1 had no version byte, v2 adds it along with
+    /// revocation and relay spend tracking. `expires_ts` and `roles` took over reserved
+    /// bytes that were always zero.
+    pub const VERSION: u8 = 2;
+
+    /// Whether the holder still counts as a member for relaying and voting.
+    pub fn is_active(&self, now: i64) -> bool {
+        !self.revoked && (self.expires_ts == 0 || now < self.expires_ts)
+    }
+
+    /// Every member holds `ROLE_MEMBER`, including records written before roles
+    /// existed; a revoked member holds no roles at all.
+    pub fn role_mask(&self) -> u8 {
+        if self.revoked {
+            return 0;
+        }
+        self.roles | ROLE_MEMBER
+    }
+
+    pub fn has_any_role(&self, roles: u8) -> bool {
+        self.role_mask() & roles != 0
+    }
+}
+
+/// `Member` as laid out before accounts carried a version byte.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MemberV1 {
+    pub dao: Pubkey,
+    pub owner: Pubkey,
+    pub membership_mint: Pubkey,
+    pub member_pass_account: Pubkey,
+    pub joined_ts: i64,
+    pub mint_count: u64,
+    pub reserved: [u8; 16],
+}
+
+impl MemberV1 {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // dao
+        + 32 // owner
+        + 32 // membership_mint
+        + 32 // pass account
+        + 8  // joined_ts
+        + 8  // mint_count
+        + 16; // reserved
+
+    /// Parses raw v1 account data, discriminator included.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 && data[..8] == Member::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    pub fn migrate(self) -> Member {
+        Member {
+            dao: self.dao,
+            owner: self.owner,
+            membership_mint: self.membership_mint,
+            member_pass_account: self.member_pass_account,
+            joined_ts: self.joined_ts,
+            mint_count: self.mint_count,
+            version: Member::VERSION,
+            ..Default::default()
+        }
+    }
+}
+
+/// Relay budget shared by every member relaying under one role.
+#[account]
+pub struct RoleBudget {
+    pub dao: Pubkey,
+    /// A single `ROLE_*` bit.
+    pub role: u8,
+    pub max_relay_spend: u64,
+    pub relay_spent: u64,
+    pub relay_epoch: u64,
+    pub relay_prev_spent: u64,
+    pub relay_prev_ts: u64,
+    pub bump: u8,
+}
+
+impl RoleBudget {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // dao
+        + 1  // role
+        + 8  // max_relay_spend
+        + 8  // relay_spent
+        + 8  // relay_epoch
+        + 8  // relay_prev_spent
+        + 8  // relay_prev_ts
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"role_budget";
+}
+
+/// A key allowed to sign relay spend for a DAO, with its own per-epoch limit.
+#[account]
+#[derive(Default)]
+pub struct Relayer {
+    pub dao: Pubkey,
+    pub relayer: Pubkey,
+    pub max_relay_spend: u64,
+    pub relay_spent: u64,
+    pub relay_epoch: u64,
+    pub relay_prev_spent: u64,
+    pub relay_prev_ts: u64,
+    /// Unix timestamp after which the key stops working; zero never expires.
+    pub expires_ts: i64,
+    pub bump: u8,
+}
+
+impl Relayer {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // dao
+        + 32 // relayer
+        + 8  // max_relay_spend
+        + 8  // relay_spent
+        + 8  // relay_epoch
+        + 8  // relay_prev_spent
+        + 8  // relay_prev_ts
+        + 8  // expires_ts
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"relayer";
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.expires_ts == 0 || now < self.expires_ts
+    }
+}
+
+/// Audit record of one sponsored relay, keyed by the relayed message hash.
+#[account]
+pub struct RelayReceipt {
+    pub dao: Pubkey,
+    /// Wallet of the member the relay was for.
+    pub member: Pubkey,
+    /// Key that signed the spend record and paid the receipt's rent.
+    pub relayer: Pubkey,
+    pub message_hash: [u8; 32],
+    pub spend: u64,
+    pub slot: u64,
+    pub recorded_ts: i64,
+    pub reimbursed: bool,
+    pub bump: u8,
+}
+
+impl RelayReceipt {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // dao
+        + 32 // member
+        + 32 // relayer
+        + 32 // message_hash
+        + 8  // spend
+        + 8  // slot
+        + 8  // recorded_ts
+        + 1  // reimbursed
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"relay_receipt";
+}
+
+/// An application to join a DAO under `JoinPolicy::Application`.
+#[account]
+pub struct JoinRequest {
+    pub dao: Pubkey,
+    pub applicant: Pubkey,
+    pub requested_ts: i64,
+    pub approved: bool,
+    pub bump: u8,
+}
+
+impl JoinRequest {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // dao
+        + 32 // applicant
+        + 8  // requested_ts
+        + 1  // approved
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"join_request";
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum RelayWindow {
+    /// Spend resets to zero once `relay_window_secs` have passed since the window opened.
+    #[default]
+    Fixed = 0,
+    /// Spend over any trailing `relay_window_secs` stays under the cap. Spend is
+    /// tracked in two buckets, so a steady trickle may get as little as half the cap.
+    Rolling = 1,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum JoinPolicy {
+    /// Only `issue_pass`, co-signed by the authority, creates members.
+    #[default]
+    AuthorityOnly = 0,
+    /// Anyone may `join_dao` by paying the dues.
+    Open = 1,
+    /// Wallets proven against `Dao.allowlist_root` may `join_dao`.
+    Allowlist = 2,
+    /// Wallets with an approved `JoinRequest` may `join_dao`.
+    Application = 3,
+}
+
+/// Program-wide emergency controls; the guardian flips `paused` bits per instruction family.
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    pub paused: u16,
+    pub bump: u8,
+    pub reserved: [u8; 16],
+}
+
+impl Config {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // admin
+        + 32 // guardian
+        + 2 // paused
+        + 1 // bump
+        + 16; // reserved
+    pub const SEED_PREFIX: &'static [u8] = b"config";
+
+    pub fn is_paused(&self, family: u16) -> bool {
+        self.paused & family != 0
+    }
+}
+
+#[error_code]
+pub enum DaoError {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Invalid limit")]
+    InvalidLimit,
+    #[msg("Invalid spend amount")]
+    InvalidSpend,
+    #[msg("Relay budget exceeded")]
+    RelayBudgetExceeded,
+    #[msg("Mint mismatch")]
+    MintMismatch,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Bump missing")]
+    BumpNotFound,
+    #[msg("Member has been revoked")]
+    MemberRevoked,
+    #[msg("Pass account does not match the member record")]
+    PassAccountMismatch,
+    #[msg("Invalid membership duration")]
+    InvalidDuration,
+    #[msg("DAO memberships do not expire")]
+    MembershipNotExpiring,
+    #[msg("DAO join policy does not allow this")]
+    JoinPolicyMismatch,
+    #[msg("Allowlist policy needs a Merkle root")]
+    InvalidAllowlistRoot,
+    #[msg("Wallet is not on the allowlist")]
+    NotAllowlisted,
+    #[msg("Join request has not been approved")]
+    JoinRequestNotApproved,
+    #[msg("Join request has already been reviewed")]
+    JoinRequestAlreadyReviewed,
+    #[msg("Join request accounts do not match the decisions")]
+    JoinRequestAccountsMismatch,
+    #[msg("Dues payer and treasury accounts are required")]
+    DuesAccountsMissing,
+    #[msg("Invalid role bits")]
+    InvalidRoles,
+    #[msg("Member does not hold the required role")]
+    MissingRole,
+    #[msg("Member is revoked or expired")]
+    MemberInactive,
+    #[msg("Member relay quota exceeded")]
+    MemberRelayQuotaExceeded,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Sponsor vault does not belong to the DAO")]
+    SponsorVaultMismatch,
+    #[msg("Account is already on the current layout")]
+    AlreadyMigrated,
+    #[msg("Relayer key has expired")]
+    RelayerExpired,
+    #[msg("Relayer spend limit exceeded")]
+    RelayerLimitExceeded,
+    #[msg("Relayers may only reimburse their own token account")]
+    RelayerAccountMismatch,
+    #[msg("Relay receipt is still within its retention period")]
+    ReceiptStillNeeded,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("DAO setup and sponsor configuration are paused")]
+    DaosPaused,
+    #[msg("Membership changes are paused")]
+    MembershipPaused,
+    #[msg("Relay spending is paused")]
+    RelayPaused,
+    #[msg("Lifetime memberships cannot be renewed")]
+    LifetimeMembership,
+    #[msg("The budget of the member's most senior budgeted role must be charged")]
+    RoleBudgetRequired,
+    #[msg("Relay receipt has already been reimbursed")]
+    AlreadyReimbursed,
+    #[msg("Account layout version is not supported")]
+    UnsupportedVersion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raw `Dao` bytes exactly as the pre-versioning program wrote them.
+    fn dao_v1_fixture() -> Vec<u8> {
+        let mut data = Vec::with_capacity(DaoV1::LEN);
+        data.extend_from_slice(&Dao::DISCRIMINATOR);
+        data.extend_from_slice(&[1u8; 32]); // authority
+        data.extend_from_slice(&[2u8; 32]); // pass_mint
+        data.extend_from_slice(&[3u8; 32]); // sponsor_mint
+        data.extend_from_slice(&[4u8; 32]); // sponsor_vault
+        data.extend_from_slice(&50_000u64.to_le_bytes()); // max_relay_spend
+        data.extend_from_slice(&1_250u64.to_le_bytes()); // relay_spent
+        data.extend_from_slice(&9_000u64.to_le_bytes()); // relay_epoch
+        data.extend_from_slice(&12u32.to_le_bytes()); // total_members
+        data.push(253); // bump
+        data.extend_from_slice(b"guild-01"); // name_seed
+        data.extend_from_slice(&[0u8; 7]); // reserved
+        data
+    }
+
+    /// Raw `Member` bytes exactly as the pre-versioning program wrote them.
+    fn member_v1_fixture() -> Vec<u8> {
+        let mut data = Vec::with_capacity(MemberV1::LEN);
+        data.extend_from_slice(&Member::DISCRIMINATOR);
+        data.extend_from_slice(&[5u8; 32]); // dao
+        data.extend_from_slice(&[6u8; 32]); // owner
+        data.extend_from_slice(&[2u8; 32]); // membership_mint
+        data.extend_from_slice(&[7u8; 32]); // member_pass_account
+        data.extend_from_slice(&1_690_000_000i64.to_le_bytes()); // joined_ts
+        data.extend_from_slice(&3u64.to_le_bytes()); // mint_count
+        data.extend_from_slice(&[0u8; 16]); // reserved
+        data
+    }
+
+    #[test]
+    fn migrates_v1_dao_fixture() {
+        let data = dao_v1_fixture();
+        assert_eq!(data.len(), DaoV1::LEN);
+
+        let dao = DaoV1::try_from_bytes(&data).unwrap().migrate();
+        let mut migrated = vec![0u8; Dao::LEN];
+        let mut writer: &mut [u8] = &mut migrated;
+        dao.try_serialize(&mut writer).unwrap();
+        let dao = Dao::try_deserialize(&mut &migrated[..]).unwrap();
+
+        assert_eq!(dao.version, Dao::VERSION);
+        assert_eq!(dao.authority, Pubkey::new_from_array([1u8; 32]));
+        assert_eq!(dao.max_relay_spend, 50_000);
+        assert_eq!(dao.relay_spent, 1_250);
+        assert_eq!(dao.total_members, 12);
+        assert_eq!(dao.bump, 253);
+        assert_eq!(&dao.name_seed, b"guild-01");
+        assert_eq!(dao.relay_window_secs, DEFAULT_RELAY_WINDOW_SECS);
+        assert!(dao.relay_window == RelayWindow::Fixed);
+    }
+
+    #[test]
+    fn migrates_v1_member_fixture() {
+        let data = member_v1_fixture();
+        assert_eq!(data.len(), MemberV1::LEN);
+
+        let member = MemberV1::try_from_bytes(&data).unwrap().migrate();
+        let mut migrated = vec![0u8; Member::LEN];
+        let mut writer: &mut [u8] = &mut migrated;
+        member.try_serialize(&mut writer).unwrap();
+        let member = Member::try_deserialize(&mut &migrated[..]).unwrap();
+
+        assert_eq!(member.version, Member::VERSION);
+        assert_eq!(member.owner, Pubkey::new_from_array([6u8; 32]));
+        assert_eq!(member.joined_ts, 1_690_000_000);
+        assert_eq!(member.mint_count, 3);
+        assert_eq!(member.expires_ts, 0);
+        assert!(member.is_active(i64::MAX));
+    }
+
+    #[test]
+    fn membership_lapses_at_expiry() {
+        let dao = Dao {
+            membership_duration_secs: 365 * 24 * 60 * 60,
+            ..Default::default()
+        };
+        let member = Member {
+            expires_ts: dao.expiry_from(1_700_000_000).unwrap(),
+            ..Default::default()
+        };
+
+        assert!(member.is_active(1_700_000_000));
+        assert!(!member.is_active(member.expires_ts));
+
+        let revoked = Member { revoked: true, ..member };
+        assert!(!revoked.is_active(1_700_000_000));
+    }
+
+    #[test]
+    fn relay_spend_respects_dao_and_member_caps() {
+        let mut dao = Dao {
+            max_relay_spend: 1_000,
+            max_member_relay_spend: 300,
+            relay_window_secs: DEFAULT_RELAY_WINDOW_SECS,
+            ..Default::default()
+        };
+        let mut alice = Member::default();
+        let mut bob = Member::default();
+        let clock = Clock {
+            unix_timestamp: 1_690_000_000,
+            ..Default::default()
+        };
+
+        charge_relay_spend(&mut dao, &mut alice, None, 300, &clock).unwrap();
+        // Failed instructions roll back on-chain, so probe the overflow on copies
+        assert!(charge_relay_spend(&mut dao.clone(), &mut alice.clone(), None, 1, &clock).is_err());
+        charge_relay_spend(&mut dao, &mut bob, None, 200, &clock).unwrap();
+        assert_eq!(dao.relay_spent, 500);
+        assert_eq!(bob.relay_spent, 200);
+
+        // A new window resets the member's quota
+        let later = Clock {
+            unix_timestamp: clock.unix_timestamp + DEFAULT_RELAY_WINDOW_SECS as i64,
+            ..Default::default()
+        };
+        charge_relay_spend(&mut dao, &mut alice, None, 300, &later).unwrap();
+        assert_eq!(alice.relay_spent, 300);
+    }
+
+    #[test]
+    fn budgeted_roles_must_be_charged() {
+        let mut dao = Dao {
+            max_relay_spend: 1_000,
+            budgeted_roles: ROLE_COUNCIL,
+            relay_window_secs: DEFAULT_RELAY_WINDOW_SECS,
+            ..Default::default()
+        };
+        let mut member = Member::default();
+        let mut council = Member { roles: ROLE_COUNCIL, ..Default::default() };
+        let mut budget = RoleBudget {
+            dao: Pubkey::default(),
+            role: ROLE_COUNCIL,
+            max_relay_spend: 100,
+            relay_spent: 0,
+            relay_epoch: 0,
+            relay_prev_spent: 0,
+            relay_prev_ts: 0,
+            bump: 0,
+        };
+        let clock = Clock {
+            unix_timestamp: 1_690_000_000,
+            ..Default::default()
+        };
+
+        charge_relay_spend(&mut dao, &mut member, None, 10, &clock).unwrap();
+        assert!(charge_relay_spend(&mut dao.clone(), &mut council.clone(), None, 10, &clock).is_err());
+        charge_relay_spend(&mut dao, &mut council, Some(&mut budget), 10, &clock).unwrap();
+        assert_eq!(budget.relay_spent, 10);
+    }
+
+    #[test]
+    fn members_with_several_roles_charge_their_most_senior_budget() {
+        let mut dao = Dao {
+            max_relay_spend: 1_000,
+            budgeted_roles: ROLE_CONTRIBUTOR | ROLE_COUNCIL,
+            relay_window_secs: DEFAULT_RELAY_WINDOW_SECS,
+            ..Default::default()
+        };
+        let mut member = Member { roles: ROLE_CONTRIBUTOR | ROLE_COUNCIL, ..Default::default() };
+        let budget_for = |role, max_relay_spend| RoleBudget {
+            dao: Pubkey::default(),
+            role,
+            max_relay_spend,
+            relay_spent: 0,
+            relay_epoch: 0,
+            relay_prev_spent: 0,
+            relay_prev_ts: 0,
+            bump: 0,
+        };
+        let mut contributor = budget_for(ROLE_CONTRIBUTOR, 500);
+        let mut council = budget_for(ROLE_COUNCIL, 50);
+        let clock = Clock {
+            unix_timestamp: 1_690_000_000,
+            ..Default::default()
+        };
+
+        // The looser contributor budget can't be picked over the council one
+        assert!(charge_relay_spend(&mut dao.clone(), &mut member.clone(), Some(&mut contributor.clone()), 100, &clock).is_err());
+        assert!(charge_relay_spend(&mut dao.clone(), &mut member.clone(), Some(&mut council.clone()), 100, &clock).is_err());
+        charge_relay_spend(&mut dao, &mut member, Some(&mut council), 50, &clock).unwrap();
+        assert_eq!(council.relay_spent, 50);
+
+        // Once council stops being budgeted the contributor budget applies
+        dao.budgeted_roles &= !ROLE_COUNCIL;
+        assert!(charge_relay_spend(&mut dao.clone(), &mut member.clone(), None, 100, &clock).is_err());
+        charge_relay_spend(&mut dao, &mut member, Some(&mut contributor), 100, &clock).unwrap();
+        assert_eq!(contributor.relay_spent, 100);
+    }
+
+    #[test]
+    fn rolling_relay_window_never_exceeds_cap() {
+        type Budget = (u64, u64, u64, u64);
+        let charge = |budget: &mut Budget, spend: u64, now: u64, mode: RelayWindow| {
+            let window = BudgetWindow { now, secs: 1_000, mode };
+            let (spent, epoch, prev_spent, prev_ts) = budget;
+            charge_relay_budget(spent, epoch, prev_spent, prev_ts, 100, spend, window, DaoError::RelayBudgetExceeded)
+        };
+
+        // Spend from half a window ago still counts in full
+        let mut rolling = (0, 0, 0, 0);
+        charge(&mut rolling, 100, 10_000, RelayWindow::Rolling).unwrap();
+        assert!(charge(&mut rolling.clone(), 50, 10_500, RelayWindow::Rolling).is_err());
+        assert!(charge(&mut rolling.clone(), 1, 10_999, RelayWindow::Rolling).is_err());
+        charge(&mut rolling, 100, 11_000, RelayWindow::Rolling).unwrap();
+
+        // Greedy spenders never fit more than the cap into any trailing window
+        for (step, spend) in [(7, 3), (37, 9), (101, 30), (250, 45)] {
+            let mut budget = (0, 0, 0, 0);
+            let mut accepted = Vec::new();
+            for now in (10_000..30_000).step_by(step) {
+                let mut attempt = budget;
+                if charge(&mut attempt, spend, now, RelayWindow::Rolling).is_ok() {
+                    budget = attempt;
+                    accepted.push((now, spend));
+                }
+            }
+            for &(end, _) in &accepted {
+                let in_window: u64 = accepted
+                    .iter()
+                    .filter(|(ts, _)| *ts <= end && end - ts < 1_000)
+                    .map(|(_, spend)| spend)
+                    .sum();
+                assert!(in_window <= 100, "{in_window} spent in the window ending at {end}");
+            }
+            // ...while still getting at least half the cap per window
+            let total: u64 = accepted.iter().map(|(_, spend)| spend).sum();
+            assert!(total >= 20 * 50 - 100, "only {total} spent with step {step}");
+        }
+
+        // A fixed window keeps the full spend until it lapses
+        assert!(charge(&mut (100, 10_000, 0, 0), 1, 10_999, RelayWindow::Fixed).is_err());
+        let mut fixed = (100, 10_000, 0, 0);
+        charge(&mut fixed, 1, 11_000, RelayWindow::Fixed).unwrap();
+        assert_eq!(fixed, (1, 11_000, 0, 0));
+    }
+
+    #[test]
+    fn relayers_are_limited_and_expire() {
+        let authority = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let dao = Dao {
+            authority,
+            relay_window_secs: DEFAULT_RELAY_WINDOW_SECS,
+            ..Default::default()
+        };
+        let mut relayer = Relayer {
+            relayer: key,
+            max_relay_spend: 100,
+            expires_ts: 1_700_000_000,
+            ..Default::default()
+        };
+        let clock = Clock {
+            unix_timestamp: 1_690_000_000,
+            ..Default::default()
+        };
+
+        authorize_relay_signer(&dao, &authority, None, 1_000, &clock).unwrap();
+        assert!(authorize_relay_signer(&dao, &key, None, 1, &clock).is_err());
+
+        authorize_relay_signer(&dao, &key, Some(&mut relayer), 100, &clock).unwrap();
+        assert!(authorize_relay_signer(&dao, &key, Some(&mut relayer.clone()), 1, &clock).is_err());
+        assert!(authorize_relay_signer(&dao, &authority, Some(&mut relayer.clone()), 1, &clock).is_err());
+
+        let expired = Clock {
+            unix_timestamp: relayer.expires_ts,
+            ..Default::default()
+        };
+        assert!(authorize_relay_signer(&dao, &key, Some(&mut relayer), 1, &expired).is_err());
+    }
+
+    #[test]
+    fn relay_receipt_len_fits_serialized_receipt() {
+        let receipt = RelayReceipt {
+            dao: Pubkey::default(),
+            member: Pubkey::default(),
+            relayer: Pubkey::default(),
+            message_hash: [0; 32],
+            spend: 0,
+            slot: 0,
+            recorded_ts: 0,
+            reimbursed: false,
+            bump: 0,
+        };
+        let mut data = Vec::new();
+        receipt.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), RelayReceipt::LEN);
+    }
+
+    #[test]
+    fn every_member_holds_the_member_role() {
+        let member = Member::default();
+        assert!(member.has_any_role(ROLE_MEMBER));
+        assert!(!member.has_any_role(ROLE_COUNCIL | ROLE_ADMIN));
+
+        let council = Member { roles: ROLE_COUNCIL, ..Default::default() };
+        assert!(council.has_any_role(ROLE_COUNCIL | ROLE_ADMIN));
+        assert_eq!(council.role_mask(), ROLE_MEMBER | ROLE_COUNCIL);
+
+        let revoked = Member { revoked: true, ..council };
+        assert_eq!(revoked.role_mask(), 0);
+    }
+
+    #[test]
+    fn verifies_sorted_pair_merkle_proofs() {
+        let leaf = |wallet: u8| keccak::hash(&[wallet; 32]).to_bytes();
+        let pair = |a: [u8; 32], b: [u8; 32]| {
+            if a <= b {
+                keccak::hashv(&[&a, &b]).to_bytes()
+            } else {
+                keccak::hashv(&[&b, &a]).to_bytes()
+            }
+        };
+        let (a, b, c) = (leaf(1), leaf(2), leaf(3));
+        let root = pair(pair(a, b), c);
+
+        assert!(verify_merkle_proof(&[b, c], root, a));
+        assert!(verify_merkle_proof(&[pair(a, b)], root, c));
+        assert!(!verify_merkle_proof(&[b, c], root, leaf(4)));
+        assert!(!verify_merkle_proof(&[], root, a));
+    }
+
+    #[test]
+    fn rejects_member_bytes_as_dao() {
+        let mut data = member_v1_fixture();
+        data.resize(DaoV1::LEN, 0);
+
+        assert!(DaoV1::try_from_bytes(&data).is_err());
+    }
 }
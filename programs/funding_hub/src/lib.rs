@@ -1,8 +1,18 @@
+use account_migration::{stored_version, write_migrated_account};
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::system_program;
 use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, CloseAccount, Mint, MintTo, Token, TokenAccount, Transfer};
+use mpl_token_metadata::accounts::Metadata as MetadataAccount;
 use mpl_token_metadata::ID as TOKEN_METADATA_ID;
+use program::FundingHub;
 
 declare_id!("B8gKYNx3LGJVpsAzY72ufrNJj6WZVf8KTodiz1Mex62u");
 
@@ -10,6 +20,91 @@ declare_id!("B8gKYNx3LGJVpsAzY72ufrNJj6WZVf8KTodiz1Mex62u");
 pub mod funding_hub {
     use super::*;
 
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        arbiter: Pubkey,
+        dispute_window_secs: i64,
+        dispute_threshold_bps: u16,
+        guardian: Pubkey,
+    ) -> Result<()> {
+        require!(dispute_window_secs >= 0, FundingError::InvalidDisputeConfig);
+        require!(
+            dispute_threshold_bps > 0 && dispute_threshold_bps <= BPS_DENOMINATOR,
+            FundingError::InvalidDisputeConfig
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.arbiter = arbiter;
+        config.dispute_window_secs = dispute_window_secs;
+        config.dispute_threshold_bps = dispute_threshold_bps;
+        config.guardian = guardian;
+        config.paused = 0;
+        config.bump = ctx.bumps.config;
+
+        Ok(())
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        arbiter: Pubkey,
+        dispute_window_secs: i64,
+        dispute_threshold_bps: u16,
+    ) -> Result<()> {
+        require!(dispute_window_secs >= 0, FundingError::InvalidDisputeConfig);
+        require!(
+            dispute_threshold_bps > 0 && dispute_threshold_bps <= BPS_DENOMINATOR,
+            FundingError::InvalidDisputeConfig
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.arbiter = arbiter;
+        config.dispute_window_secs = dispute_window_secs;
+        config.dispute_threshold_bps = dispute_threshold_bps;
+
+        Ok(())
+    }
+
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.config.guardian = guardian;
+
+        Ok(())
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, paused: u16) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, FundingError::InvalidPauseFlags);
+        ctx.accounts.config.paused = paused;
+
+        Ok(())
+    }
+
+    pub fn migrate_project(ctx: Context<MigrateProject>) -> Result<()> {
+        let project_info = ctx.accounts.project.to_account_info();
+        let legacy = {
+            let data = project_info.try_borrow_data()?;
+            match stored_version(&data, ProjectV1::LEN, |project: &Project| project.version)? {
+                1 => ProjectV1::try_from_bytes(&data)?,
+                Project::VERSION => return err!(FundingError::AlreadyMigrated),
+                _ => return err!(FundingError::UnsupportedVersion),
+            }
+        };
+
+        require_keys_eq!(
+            ctx.accounts.project_vault.key(),
+            legacy.vault,
+            FundingError::VaultMismatch
+        );
+
+        let migrated = legacy.migrate(ctx.accounts.project_vault.amount);
+        write_migrated_account(
+            &project_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Project::LEN,
+            &migrated,
+        )
+    }
+
     pub fn initialize_project(
         ctx: Context<InitializeProject>,
         project_id: u64,
@@ -29,29 +124,38 @@ pub mod funding_hub {
         let clock = Clock::get()?;
         require!(deadline_ts > clock.unix_timestamp, FundingError::InvalidDeadline);
 
-        require_keys_eq!(
-            ctx.accounts.token_metadata_program.key(),
-            TOKEN_METADATA_ID,
-            FundingError::MetadataProgramMismatch
-        );
-
         let project_id_bytes = project_id.to_le_bytes();
 
         let project = &mut ctx.accounts.project;
         project.project_id = project_id;
         project.project_id_seed = project_id_bytes;
         project.authority = ctx.accounts.authority.key();
+        project.creator = ctx.accounts.authority.key();
+        project.pending_authority = Pubkey::default();
         project.mint = ctx.accounts.mint.key();
         project.badge_mint = ctx.accounts.badge_mint.key();
         project.vault = ctx.accounts.project_vault.key();
         project.target_amount = target_amount;
         project.deadline_ts = deadline_ts;
         project.total_pledged = 0;
+        project.total_weighted = 0;
+        project.early_bird_window_count = 0;
+        project.referral_reward_bps = 0;
+        project.total_referred = 0;
+        project.referral_rewards_claimed = 0;
+        project.payout_split_enabled = false;
+        project.total_withdrawn = 0;
+        project.total_refunded = 0;
+        project.update_count = 0;
+        project.dispute_window_end_ts = 0;
+        project.dispute_status = DisputeStatus::None;
+        project.referral_reward_pool = 0;
         project.status = ProjectStatus::Active;
+        project.version = Project::VERSION;
         project.bump = ctx.bumps.project;
         let bump = project.bump;
         let project_id_bytes = project.project_id_seed;
-        let authority_pubkey = project.authority;
+        let creator_pubkey = project.creator;
 
         // Create metadata using mpl-token-metadata v4 API
         let metadata_ix = mpl_token_metadata::instructions::CreateV1 {
@@ -62,7 +166,7 @@ pub mod funding_hub {
             payer: ctx.accounts.authority.key(),
             update_authority: (ctx.accounts.project.key(), true),
             system_program: ctx.accounts.system_program.key(),
-            sysvar_instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            sysvar_instructions: ctx.accounts.sysvar_instructions.key(),
             spl_token_program: Some(ctx.accounts.token_program.key()),
         }
         .instruction(mpl_token_metadata::instructions::CreateV1InstructionArgs {
@@ -85,7 +189,7 @@ pub mod funding_hub {
         let signer_seeds: &[&[&[u8]]] = &[
             &[
                 Project::SEED_PREFIX,
-                authority_pubkey.as_ref(),
+                creator_pubkey.as_ref(),
                 &project_id_bytes,
                 &[bump],
             ]
@@ -99,6 +203,7 @@ pub mod funding_hub {
             ctx.accounts.system_program.to_account_info(),
             ctx.accounts.sysvar_instructions.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.token_metadata_program.to_account_info(),
         ];
 
         invoke_signed(&metadata_ix, &account_infos, signer_seeds)?;
@@ -106,9 +211,100 @@ pub mod funding_hub {
         Ok(())
     }
 
-    pub fn pledge(ctx: Context<Pledge>, amount: u64) -> Result<()> {
+    pub fn set_early_bird_schedule(
+        ctx: Context<SetEarlyBirdSchedule>,
+        windows: Vec<EarlyBirdWindow>,
+    ) -> Result<()> {
+        require!(
+            windows.len() <= MAX_EARLY_BIRD_WINDOWS,
+            FundingError::TooManyEarlyBirdWindows
+        );
+
+        let project = &mut ctx.accounts.project;
+        require!(
+            matches!(project.status, ProjectStatus::Active),
+            FundingError::ProjectNotActive
+        );
+        // Changing the bonus after backers have committed would rewrite their terms
+        require!(project.total_pledged == 0, FundingError::PledgesAlreadyReceived);
+
+        let clock = Clock::get()?;
+        let mut previous: Option<&EarlyBirdWindow> = None;
+        for window in windows.iter() {
+            require!(
+                window.end_ts > clock.unix_timestamp && window.end_ts <= project.deadline_ts,
+                FundingError::InvalidEarlyBirdWindow
+            );
+            require!(
+                window.multiplier_bps >= BPS_DENOMINATOR
+                    && window.multiplier_bps <= MAX_EARLY_BIRD_MULTIPLIER_BPS,
+                FundingError::InvalidEarlyBirdMultiplier
+            );
+            if let Some(previous) = previous {
+                // Windows are ordered by end time and earlier backers never earn less
+                require!(
+                    window.end_ts > previous.end_ts
+                        && window.multiplier_bps <= previous.multiplier_bps,
+                    FundingError::InvalidEarlyBirdWindow
+                );
+            }
+            previous = Some(window);
+        }
+
+        project.early_bird_windows = [EarlyBirdWindow::default(); MAX_EARLY_BIRD_WINDOWS];
+        project.early_bird_windows[..windows.len()].copy_from_slice(&windows);
+        project.early_bird_window_count = windows.len() as u8;
+
+        Ok(())
+    }
+
+    pub fn set_referral_reward(ctx: Context<SetReferralReward>, reward_bps: u16) -> Result<()> {
+        require!(
+            reward_bps <= MAX_REFERRAL_REWARD_BPS,
+            FundingError::InvalidReferralReward
+        );
+
+        let project = &mut ctx.accounts.project;
+        require!(
+            matches!(project.status, ProjectStatus::Active),
+            FundingError::ProjectNotActive
+        );
+        require!(project.total_pledged == 0, FundingError::PledgesAlreadyReceived);
+
+        project.referral_reward_bps = reward_bps;
+
+        Ok(())
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        require!(
+            matches!(ctx.accounts.project.status, ProjectStatus::Active),
+            FundingError::ProjectNotActive
+        );
+
+        let referral = &mut ctx.accounts.referral;
+        referral.project = ctx.accounts.project.key();
+        referral.referrer = ctx.accounts.referrer.key();
+        referral.total_referred = 0;
+        referral.referred_pledges = 0;
+        referral.reward_claimed = false;
+        referral.bump = ctx.bumps.referral;
+
+        Ok(())
+    }
+
+    pub fn pledge(ctx: Context<Pledge>, amount: u64, referrer: Option<Pubkey>) -> Result<()> {
         require!(amount > 0, FundingError::InvalidAmount);
 
+        match (referrer, ctx.accounts.referral.as_ref()) {
+            (Some(referrer), Some(referral)) => {
+                require_keys_eq!(referral.referrer, referrer, FundingError::ReferralMismatch);
+                require_keys_neq!(referrer, ctx.accounts.donor.key(), FundingError::SelfReferral);
+            }
+            (None, None) => {}
+            _ => return err!(FundingError::ReferralMismatch),
+        }
+
         let project = &mut ctx.accounts.project;
         require!(
             matches!(project.status, ProjectStatus::Active),
@@ -138,24 +334,66 @@ pub mod funding_hub {
         token::transfer(cpi_ctx, amount)?;
 
         let should_mint_badge = ctx.accounts.donor_badge_account.amount == 0;
-        let project_id_seed = project.project_id_seed;
-        let project_authority = project.authority;
-        let project_bump = project.bump;
+        let project_key = project.key();
+        credit_pledge(
+            project,
+            project_key,
+            &mut ctx.accounts.donation,
+            ctx.accounts.donor.key(),
+            ctx.bumps.donation,
+            amount,
+            referrer.is_some(),
+            clock.unix_timestamp,
+        )?;
 
-        project.total_pledged = project
-            .total_pledged
-            .checked_add(amount)
-            .ok_or(FundingError::MathOverflow)?;
+        if let Some(reward_tier) = ctx.accounts.reward_tier.as_mut() {
+            require!(amount >= reward_tier.price, FundingError::PledgeBelowRewardPrice);
+            require!(
+                reward_tier.claimed < reward_tier.total_supply,
+                FundingError::RewardTierSoldOut
+            );
+            let donation = &mut ctx.accounts.donation;
+            // One perk per backer per project, like a traditional crowdfunding reward
+            require!(donation.reward_tier.is_none(), FundingError::RewardAlreadyClaimed);
 
-        drop(project);
+            reward_tier.claimed = reward_tier
+                .claimed
+                .checked_add(1)
+                .ok_or(FundingError::MathOverflow)?;
+            donation.reward_tier = Some(reward_tier.tier_id);
+            donation.reward_fulfilled = false;
+        }
+
+        if let Some(referral) = ctx.accounts.referral.as_mut() {
+            let project = &mut ctx.accounts.project;
+            // Grow the pool by exactly what this referrer's claim grows by, so rounding
+            // never leaves unclaimable dust in the vault
+            let reward_before = apply_bps(referral.total_referred, project.referral_reward_bps)?;
+            referral.total_referred = referral
+                .total_referred
+                .checked_add(amount)
+                .ok_or(FundingError::MathOverflow)?;
+            referral.referred_pledges = referral
+                .referred_pledges
+                .checked_add(1)
+                .ok_or(FundingError::MathOverflow)?;
+            let reward_after = apply_bps(referral.total_referred, project.referral_reward_bps)?;
+            project.referral_reward_pool = project
+                .referral_reward_pool
+                .checked_add(reward_after - reward_before)
+                .ok_or(FundingError::MathOverflow)?;
+        }
 
         if should_mint_badge {
+            let project = &ctx.accounts.project;
+            let project_id_bytes = project.project_id_seed;
+            let bump = [project.bump];
             let signer_seeds: &[&[&[u8]]] = &[
                 &[
                     Project::SEED_PREFIX,
-                    project_authority.as_ref(),
-                    &project_id_seed,
-                    &[project_bump],
+                    project.creator.as_ref(),
+                    &project_id_bytes,
+                    &bump,
                 ]
             ];
 
@@ -171,174 +409,2364 @@ pub mod funding_hub {
             token::mint_to(mint_badge_ctx, 1)?;
         }
 
-        Ok(())
+        refresh_minted_badge(
+            &ctx.accounts.project,
+            &mut ctx.accounts.donation,
+            DonorBadgeAccounts {
+                badge_config: ctx.accounts.badge_config.as_ref(),
+                donor_badge_mint: ctx.accounts.donor_badge_mint.as_ref(),
+                donor_badge_metadata: ctx.accounts.donor_badge_metadata.as_ref(),
+                token_metadata_program: ctx.accounts.token_metadata_program.as_ref(),
+            },
+            &ctx.accounts.donor.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts
+                .sysvar_instructions
+                .as_ref()
+                .map(|sysvar| sysvar.to_account_info()),
+        )
     }
 
-    pub fn finalize_project(ctx: Context<FinalizeProject>) -> Result<()> {
-        let project = &mut ctx.accounts.project;
+    pub fn create_reward_tier(
+        ctx: Context<CreateRewardTier>,
+        tier_id: u8,
+        price: u64,
+        total_supply: u32,
+        fulfilment_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(price > 0, FundingError::InvalidAmount);
+        require!(total_supply > 0, FundingError::InvalidRewardSupply);
         require!(
-            matches!(project.status, ProjectStatus::Active),
-            FundingError::ProjectAlreadyClosed
+            matches!(ctx.accounts.project.status, ProjectStatus::Active),
+            FundingError::ProjectNotActive
         );
 
-        let clock = Clock::get()?;
+        let reward_tier = &mut ctx.accounts.reward_tier;
+        reward_tier.project = ctx.accounts.project.key();
+        reward_tier.authority = ctx.accounts.authority.key();
+        reward_tier.tier_id = tier_id;
+        reward_tier.price = price;
+        reward_tier.total_supply = total_supply;
+        reward_tier.claimed = 0;
+        reward_tier.fulfilled = 0;
+        reward_tier.fulfilment_hash = fulfilment_hash;
+        reward_tier.bump = ctx.bumps.reward_tier;
+
+        Ok(())
+    }
+
+    pub fn mark_reward_fulfilled(ctx: Context<MarkRewardFulfilled>) -> Result<()> {
+        let donation = &mut ctx.accounts.donation;
+        let reward_tier = &mut ctx.accounts.reward_tier;
         require!(
-            clock.unix_timestamp >= project.deadline_ts
-                || project.total_pledged >= project.target_amount,
-            FundingError::ProjectStillRunning
+            donation.reward_tier == Some(reward_tier.tier_id),
+            FundingError::NoRewardClaimed
         );
+        require!(!donation.reward_fulfilled, FundingError::RewardAlreadyFulfilled);
 
-        project.status = if project.total_pledged >= project.target_amount {
-            ProjectStatus::Successful
-        } else {
-            ProjectStatus::Failed
-        };
+        donation.reward_fulfilled = true;
+        reward_tier.fulfilled = reward_tier
+            .fulfilled
+            .checked_add(1)
+            .ok_or(FundingError::MathOverflow)?;
 
         Ok(())
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-        require!(amount > 0, FundingError::InvalidAmount);
-
-        let project = &ctx.accounts.project;
-        require!(
-            matches!(project.status, ProjectStatus::Successful),
-            FundingError::ProjectNotSuccessful
-        );
+    pub fn configure_donor_badges(
+        ctx: Context<ConfigureDonorBadges>,
+        name: String,
+        symbol: String,
+        base_uri: String,
+        tier_thresholds: [u64; BADGE_TIER_COUNT - 1],
+    ) -> Result<()> {
+        require!(name.len() <= BadgeConfig::MAX_NAME_LEN, FundingError::NameTooLong);
+        require!(symbol.len() <= BadgeConfig::MAX_SYMBOL_LEN, FundingError::SymbolTooLong);
+        require!(base_uri.len() <= BadgeConfig::MAX_BASE_URI_LEN, FundingError::UriTooLong);
         require!(
-            ctx.accounts.project_vault.amount >= amount,
-            FundingError::VaultBalanceTooLow
+            tier_thresholds[0] > 0 && tier_thresholds.windows(2).all(|pair| pair[0] < pair[1]),
+            FundingError::InvalidBadgeTiers
         );
 
+        let badge_config = &mut ctx.accounts.badge_config;
+        badge_config.project = ctx.accounts.project.key();
+        badge_config.name = name;
+        badge_config.symbol = symbol;
+        badge_config.base_uri = base_uri;
+        badge_config.tier_thresholds = tier_thresholds;
+        badge_config.bump = ctx.bumps.badge_config;
+
+        Ok(())
+    }
+
+    pub fn mint_donor_badge(ctx: Context<MintDonorBadge>) -> Result<()> {
+        require!(!ctx.accounts.donation.refunded, FundingError::AlreadyRefunded);
+
+        let project = &ctx.accounts.project;
         let project_id_bytes = project.project_id_seed;
         let bump = [project.bump];
         let signer_seeds: &[&[&[u8]]] = &[
             &[
                 Project::SEED_PREFIX,
-                project.authority.as_ref(),
+                project.creator.as_ref(),
                 &project_id_bytes,
                 &bump,
             ]
         ];
 
-        let cpi_ctx = CpiContext::new_with_signer(
+        let mint_badge_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.project_vault.to_account_info(),
-                to: ctx.accounts.authority_token_account.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.donor_badge_mint.to_account_info(),
+                to: ctx.accounts.donor_badge_token.to_account_info(),
                 authority: ctx.accounts.project.to_account_info(),
             },
             signer_seeds,
         );
-        token::transfer(cpi_ctx, amount)?;
+        token::mint_to(mint_badge_ctx, 1)?;
+
+        let donation = &mut ctx.accounts.donation;
+        let badge_config = &ctx.accounts.badge_config;
+        donation.badge_tier = badge_config.tier_for(donation.weighted_amount);
+        donation.badge_minted = true;
+        let (name, uri) = badge_config.metadata_for(donation);
+
+        let metadata_ix = mpl_token_metadata::instructions::CreateV1 {
+            metadata: ctx.accounts.donor_badge_metadata.key(),
+            master_edition: None,
+            mint: (ctx.accounts.donor_badge_mint.key(), false),
+            authority: ctx.accounts.project.key(),
+            payer: ctx.accounts.donor.key(),
+            update_authority: (ctx.accounts.project.key(), true),
+            system_program: ctx.accounts.system_program.key(),
+            sysvar_instructions: ctx.accounts.sysvar_instructions.key(),
+            spl_token_program: Some(ctx.accounts.token_program.key()),
+        }
+        .instruction(mpl_token_metadata::instructions::CreateV1InstructionArgs {
+            name,
+            symbol: badge_config.symbol.clone(),
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            primary_sale_happened: false,
+            is_mutable: true,
+            token_standard: mpl_token_metadata::types::TokenStandard::NonFungible,
+            collection: None,
+            uses: None,
+            collection_details: None,
+            rule_set: None,
+            decimals: Some(0),
+            print_supply: None,
+        });
+
+        let account_infos = [
+            ctx.accounts.donor_badge_metadata.to_account_info(),
+            ctx.accounts.donor_badge_mint.to_account_info(),
+            ctx.accounts.project.to_account_info(),
+            ctx.accounts.donor.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sysvar_instructions.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.token_metadata_program.to_account_info(),
+        ];
+
+        invoke_signed(&metadata_ix, &account_infos, signer_seeds)?;
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(project_id: u64)]
-pub struct InitializeProject<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    #[account(
-        init,
-        payer = authority,
-        space = Project::LEN,
-        seeds = [Project::SEED_PREFIX, authority.key().as_ref(), &project_id.to_le_bytes()],
-        bump
-    )]
-    pub project: Account<'info, Project>,
-    pub mint: Account<'info, Mint>,
-    #[account(
-        init,
-        payer = authority,
-        associated_token::mint = mint,
-        associated_token::authority = project
-    )]
-    pub project_vault: Account<'info, TokenAccount>,
-    #[account(
-        init,
-        payer = authority,
-        mint::decimals = 0,
-        mint::authority = project,
-        mint::freeze_authority = project
-    )]
-    pub badge_mint: Account<'info, Mint>,
-    /// CHECK: Created via Metaplex CPI
-    #[account(mut)]
-    pub badge_metadata: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    /// CHECK: Metaplex Token Metadata program
-    pub token_metadata_program: UncheckedAccount<'info>,
-    pub rent: Sysvar<'info, Rent>,
-    /// CHECK: Sysvar Instructions
-    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
-    pub sysvar_instructions: UncheckedAccount<'info>,
-}
+    pub fn refresh_donor_badge(ctx: Context<RefreshDonorBadge>) -> Result<()> {
+        update_badge_metadata(
+            &ctx.accounts.project,
+            &ctx.accounts.badge_config,
+            &mut ctx.accounts.donation,
+            &ctx.accounts.donor_badge_mint.to_account_info(),
+            &ctx.accounts.donor_badge_metadata.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.sysvar_instructions.to_account_info(),
+            &ctx.accounts.token_metadata_program.to_account_info(),
+        )
+    }
 
-#[derive(Accounts)]
-pub struct Pledge<'info> {
-    #[account(mut)]
-    pub donor: Signer<'info>,
-    #[account(
-        mut,
-        constraint = donor_token_account.owner == donor.key() @ FundingError::Unauthorized
-    )]
-    pub donor_token_account: Account<'info, TokenAccount>,
-    #[account(
-        mut,
-        seeds = [Project::SEED_PREFIX, project.authority.as_ref(), project.project_id_seed.as_ref()],
-        bump
-    )]
-    pub project: Account<'info, Project>,
-    #[account(
-        mut,
-        constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
-    )]
-    pub project_vault: Account<'info, TokenAccount>,
-    #[account(
-        mut,
-        address = project.badge_mint @ FundingError::MintMismatch
-    )]
-    pub badge_mint: Account<'info, Mint>,
-    #[account(
-        init_if_needed,
-        payer = donor,
-        associated_token::mint = badge_mint,
-        associated_token::authority = donor
-    )]
-    pub donor_badge_account: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
-}
+    pub fn register_partner(ctx: Context<RegisterPartner>, signer: Pubkey) -> Result<()> {
+        let partner = &mut ctx.accounts.partner;
+        partner.signer = signer;
+        partner.active = true;
+        partner.total_redeemed = 0;
+        partner.bump = ctx.bumps.partner;
 
-#[derive(Accounts)]
-pub struct FinalizeProject<'info> {
-    pub authority: Signer<'info>,
-    #[account(
-        mut,
-        has_one = authority @ FundingError::Unauthorized,
-        seeds = [Project::SEED_PREFIX, project.authority.as_ref(), project.project_id_seed.as_ref()],
-        bump
-    )]
-    pub project: Account<'info, Project>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct Withdraw<'info> {
-    pub authority: Signer<'info>,
-    #[account(
-        mut,
-        has_one = authority @ FundingError::Unauthorized,
-        seeds = [Project::SEED_PREFIX, project.authority.as_ref(), project.project_id_seed.as_ref()],
-        bump
+    pub fn set_partner_active(ctx: Context<SetPartnerActive>, active: bool) -> Result<()> {
+        ctx.accounts.partner.active = active;
+
+        Ok(())
+    }
+
+    pub fn redeem_pledge_voucher(
+        ctx: Context<RedeemPledgeVoucher>,
+        amount: u64,
+        nonce: u64,
+    ) -> Result<()> {
+        require!(amount > 0, FundingError::InvalidAmount);
+        require!(ctx.accounts.partner.active, FundingError::PartnerInactive);
+
+        let project = &ctx.accounts.project;
+        require!(
+            matches!(project.status, ProjectStatus::Active),
+            FundingError::ProjectNotActive
+        );
+
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp < project.deadline_ts,
+            FundingError::ProjectEnded
+        );
+
+        let message = voucher_message(&project.key(), &ctx.accounts.donor.key(), amount, nonce);
+        verify_ed25519_signature(
+            &ctx.accounts.sysvar_instructions.to_account_info(),
+            &ctx.accounts.partner.signer,
+            &message,
+        )?;
+
+        // The partner approves its PDA as delegate on the funding account beforehand
+        require!(
+            amount <= ctx.accounts.partner_funding_account.delegated_amount,
+            FundingError::VoucherExceedsAllowance
+        );
+        let partner = &ctx.accounts.partner;
+        let partner_bump = [partner.bump];
+        let partner_seeds: &[&[&[u8]]] = &[
+            &[
+                Partner::SEED_PREFIX,
+                partner.signer.as_ref(),
+                &partner_bump,
+            ]
+        ];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.partner_funding_account.to_account_info(),
+                to: ctx.accounts.project_vault.to_account_info(),
+                authority: ctx.accounts.partner.to_account_info(),
+            },
+            partner_seeds,
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        let voucher_nonce = &mut ctx.accounts.voucher_nonce;
+        voucher_nonce.partner = ctx.accounts.partner.key();
+        voucher_nonce.nonce = nonce;
+        voucher_nonce.project = project.key();
+        voucher_nonce.donor = ctx.accounts.donor.key();
+        voucher_nonce.amount = amount;
+        voucher_nonce.redeemed_ts = clock.unix_timestamp;
+        voucher_nonce.bump = ctx.bumps.voucher_nonce;
+
+        let partner = &mut ctx.accounts.partner;
+        partner.total_redeemed = partner
+            .total_redeemed
+            .checked_add(amount)
+            .ok_or(FundingError::MathOverflow)?;
+
+        let should_mint_badge = ctx.accounts.donor_badge_account.amount == 0;
+        let project_key = ctx.accounts.project.key();
+        credit_pledge(
+            &mut ctx.accounts.project,
+            project_key,
+            &mut ctx.accounts.donation,
+            ctx.accounts.donor.key(),
+            ctx.bumps.donation,
+            amount,
+            false,
+            clock.unix_timestamp,
+        )?;
+
+        if should_mint_badge {
+            let project = &ctx.accounts.project;
+            let project_id_bytes = project.project_id_seed;
+            let bump = [project.bump];
+            let signer_seeds: &[&[&[u8]]] = &[
+                &[
+                    Project::SEED_PREFIX,
+                    project.creator.as_ref(),
+                    &project_id_bytes,
+                    &bump,
+                ]
+            ];
+
+            let mint_badge_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.badge_mint.to_account_info(),
+                    to: ctx.accounts.donor_badge_account.to_account_info(),
+                    authority: ctx.accounts.project.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::mint_to(mint_badge_ctx, 1)?;
+        }
+
+        refresh_minted_badge(
+            &ctx.accounts.project,
+            &mut ctx.accounts.donation,
+            DonorBadgeAccounts {
+                badge_config: ctx.accounts.badge_config.as_ref(),
+                donor_badge_mint: ctx.accounts.donor_badge_mint.as_ref(),
+                donor_badge_metadata: ctx.accounts.donor_badge_metadata.as_ref(),
+                token_metadata_program: ctx.accounts.token_metadata_program.as_ref(),
+            },
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            Some(ctx.accounts.sysvar_instructions.to_account_info()),
+        )
+    }
+
+    pub fn set_beneficiaries(
+        ctx: Context<SetBeneficiaries>,
+        beneficiaries: Vec<Beneficiary>,
+    ) -> Result<()> {
+        require!(
+            beneficiaries.len() <= MAX_BENEFICIARIES,
+            FundingError::TooManyBeneficiaries
+        );
+
+        let project = &mut ctx.accounts.project;
+        // Splits are locked once the campaign has been finalized
+        require!(
+            matches!(project.status, ProjectStatus::Active),
+            FundingError::ProjectNotActive
+        );
+
+        let mut total_share_bps: u32 = 0;
+        for (index, beneficiary) in beneficiaries.iter().enumerate() {
+            require!(beneficiary.share_bps > 0, FundingError::InvalidBeneficiaryShares);
+            require!(
+                beneficiaries[..index]
+                    .iter()
+                    .all(|other| other.wallet != beneficiary.wallet),
+                FundingError::DuplicateBeneficiary
+            );
+            total_share_bps += beneficiary.share_bps as u32;
+        }
+        // An empty list switches payouts back to the authority's `withdraw`
+        require!(
+            beneficiaries.is_empty() || total_share_bps == BPS_DENOMINATOR as u32,
+            FundingError::InvalidBeneficiaryShares
+        );
+
+        project.payout_split_enabled = !beneficiaries.is_empty();
+
+        let payout_split = &mut ctx.accounts.payout_split;
+        payout_split.project = project.key();
+        payout_split.beneficiaries = beneficiaries;
+        payout_split.bump = ctx.bumps.payout_split;
+
+        Ok(())
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        // Proposing the default key cancels a pending transfer
+        ctx.accounts.project.pending_authority = new_authority;
+
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let project = &mut ctx.accounts.project;
+        require!(
+            project.pending_authority != Pubkey::default(),
+            FundingError::NoPendingAuthority
+        );
+        require_keys_eq!(
+            project.pending_authority,
+            ctx.accounts.new_authority.key(),
+            FundingError::Unauthorized
+        );
+
+        project.authority = project.pending_authority;
+        project.pending_authority = Pubkey::default();
+
+        Ok(())
+    }
+
+    pub fn post_update(
+        ctx: Context<PostUpdate>,
+        content_hash: [u8; 32],
+        uri: String,
+    ) -> Result<()> {
+        require!(uri.len() <= ProjectUpdate::MAX_URI_LEN, FundingError::UriTooLong);
+
+        let project = &mut ctx.accounts.project;
+        let update = &mut ctx.accounts.update;
+        update.project = project.key();
+        update.authority = ctx.accounts.authority.key();
+        update.index = project.update_count;
+        update.content_hash = content_hash;
+        update.uri = uri;
+        update.posted_ts = Clock::get()?.unix_timestamp;
+        update.bump = ctx.bumps.update;
+
+        project.update_count = project
+            .update_count
+            .checked_add(1)
+            .ok_or(FundingError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn finalize_project(ctx: Context<FinalizeProject>) -> Result<()> {
+        let project = &mut ctx.accounts.project;
+        require!(
+            matches!(project.status, ProjectStatus::Active),
+            FundingError::ProjectAlreadyClosed
+        );
+
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= project.deadline_ts
+                || project.total_pledged >= project.target_amount,
+            FundingError::ProjectStillRunning
+        );
+
+        project.status = if project.total_pledged >= project.target_amount {
+            ProjectStatus::Successful
+        } else {
+            ProjectStatus::Failed
+        };
+
+        if matches!(project.status, ProjectStatus::Successful) {
+            // Payouts stay locked until donors have had a chance to dispute
+            project.dispute_window_end_ts = clock
+                .unix_timestamp
+                .checked_add(ctx.accounts.config.dispute_window_secs)
+                .ok_or(FundingError::MathOverflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn open_dispute(ctx: Context<OpenDispute>, reason_hash: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let project = &mut ctx.accounts.project;
+        require!(
+            matches!(project.status, ProjectStatus::Successful),
+            FundingError::ProjectNotSuccessful
+        );
+        require!(
+            clock.unix_timestamp < project.dispute_window_end_ts,
+            FundingError::DisputeWindowClosed
+        );
+        require!(
+            project.dispute_status == DisputeStatus::None,
+            FundingError::DisputeAlreadyOpened
+        );
+
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.project = project.key();
+        dispute.opened_by = ctx.accounts.donor.key();
+        dispute.reason_hash = reason_hash;
+        dispute.opened_ts = clock.unix_timestamp;
+        dispute.backing_weight = 0;
+        dispute.backers = 0;
+        dispute.resolution_hash = [0; 32];
+        dispute.resolved_ts = 0;
+        dispute.bump = ctx.bumps.dispute;
+
+        project.dispute_status = DisputeStatus::Pending;
+
+        back_dispute_with(
+            project,
+            dispute,
+            &mut ctx.accounts.donation,
+            ctx.accounts.config.dispute_threshold_bps,
+        )
+    }
+
+    pub fn back_dispute(ctx: Context<BackDispute>) -> Result<()> {
+        let clock = Clock::get()?;
+        let project = &mut ctx.accounts.project;
+        require!(
+            clock.unix_timestamp < project.dispute_window_end_ts,
+            FundingError::DisputeWindowClosed
+        );
+        require!(
+            matches!(project.dispute_status, DisputeStatus::Pending | DisputeStatus::Open),
+            FundingError::NoOpenDispute
+        );
+
+        back_dispute_with(
+            project,
+            &mut ctx.accounts.dispute,
+            &mut ctx.accounts.donation,
+            ctx.accounts.config.dispute_threshold_bps,
+        )
+    }
+
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        refund_donors: bool,
+        resolution_hash: [u8; 32],
+    ) -> Result<()> {
+        let project = &mut ctx.accounts.project;
+        require!(
+            project.dispute_status == DisputeStatus::Open,
+            FundingError::NoOpenDispute
+        );
+
+        if refund_donors {
+            project.dispute_status = DisputeStatus::Refunded;
+            project.status = ProjectStatus::Refunding;
+        } else {
+            project.dispute_status = DisputeStatus::Released;
+        }
+
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.resolution_hash = resolution_hash;
+        dispute.resolved_ts = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        require!(amount > 0, FundingError::InvalidAmount);
+
+        let project = &ctx.accounts.project;
+        // Split projects pay out only through `distribute`
+        require!(!project.payout_split_enabled, FundingError::PayoutSplitActive);
+        require!(
+            matches!(project.status, ProjectStatus::Successful),
+            FundingError::ProjectNotSuccessful
+        );
+        require!(
+            project.payouts_unlocked(Clock::get()?.unix_timestamp),
+            FundingError::PayoutsLocked
+        );
+        require!(amount <= project.withdrawable()?, FundingError::WithdrawalExceedsRaised);
+        require!(
+            ctx.accounts.project_vault.amount >= amount,
+            FundingError::VaultBalanceTooLow
+        );
+
+        let project_id_bytes = project.project_id_seed;
+        let bump = [project.bump];
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Project::SEED_PREFIX,
+                project.creator.as_ref(),
+                &project_id_bytes,
+                &bump,
+            ]
+        ];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.project_vault.to_account_info(),
+                to: ctx.accounts.authority_token_account.to_account_info(),
+                authority: ctx.accounts.project.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        let project = &mut ctx.accounts.project;
+        project.total_withdrawn = project
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(FundingError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn distribute<'info>(ctx: Context<'_, '_, 'info, 'info, Distribute<'info>>) -> Result<()> {
+        let project = &ctx.accounts.project;
+        require!(
+            matches!(project.status, ProjectStatus::Successful),
+            FundingError::ProjectNotSuccessful
+        );
+        require!(
+            project.payouts_unlocked(Clock::get()?.unix_timestamp),
+            FundingError::PayoutsLocked
+        );
+        require!(project.payout_split_enabled, FundingError::PayoutSplitNotConfigured);
+
+        let beneficiaries = &ctx.accounts.payout_split.beneficiaries;
+        require!(
+            ctx.remaining_accounts.len() == beneficiaries.len(),
+            FundingError::BeneficiaryAccountsMismatch
+        );
+
+        let distributable = project.withdrawable()?;
+        require!(distributable > 0, FundingError::NothingToClaim);
+        require!(
+            ctx.accounts.project_vault.amount >= distributable,
+            FundingError::VaultBalanceTooLow
+        );
+
+        let project_id_bytes = project.project_id_seed;
+        let bump = [project.bump];
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Project::SEED_PREFIX,
+                project.creator.as_ref(),
+                &project_id_bytes,
+                &bump,
+            ]
+        ];
+
+        let mut remaining = distributable;
+        for (index, (beneficiary, token_account_info)) in beneficiaries
+            .iter()
+            .zip(ctx.remaining_accounts.iter())
+            .enumerate()
+        {
+            let token_account = Account::<TokenAccount>::try_from(token_account_info)?;
+            require_keys_eq!(token_account.owner, beneficiary.wallet, FundingError::BeneficiaryAccountsMismatch);
+            require_keys_eq!(token_account.mint, project.mint, FundingError::MintMismatch);
+
+            // The last beneficiary absorbs rounding dust so the vault is fully split
+            let share = if index + 1 == beneficiaries.len() {
+                remaining
+            } else {
+                apply_bps(distributable, beneficiary.share_bps)?
+            };
+            remaining = remaining
+                .checked_sub(share)
+                .ok_or(FundingError::MathOverflow)?;
+            if share == 0 {
+                continue;
+            }
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.project_vault.to_account_info(),
+                    to: token_account_info.clone(),
+                    authority: ctx.accounts.project.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer(cpi_ctx, share)?;
+        }
+
+        let project = &mut ctx.accounts.project;
+        project.total_withdrawn = project
+            .total_withdrawn
+            .checked_add(distributable)
+            .ok_or(FundingError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        let project = &ctx.accounts.project;
+        require!(
+            matches!(project.status, ProjectStatus::Failed | ProjectStatus::Refunding),
+            FundingError::ProjectNotRefundable
+        );
+
+        let donation = &ctx.accounts.donation;
+        require!(!donation.refunded, FundingError::AlreadyRefunded);
+        // Refunds pay back raw amounts; early-bird weighting never changes what a donor gets back
+        let amount = donation.amount;
+        require!(amount > 0, FundingError::NothingToClaim);
+
+        let project_id_bytes = project.project_id_seed;
+        let bump = [project.bump];
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Project::SEED_PREFIX,
+                project.creator.as_ref(),
+                &project_id_bytes,
+                &bump,
+            ]
+        ];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.project_vault.to_account_info(),
+                to: ctx.accounts.donor_token_account.to_account_info(),
+                authority: ctx.accounts.project.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        let project = &mut ctx.accounts.project;
+        project.total_refunded = project
+            .total_refunded
+            .checked_add(amount)
+            .ok_or(FundingError::MathOverflow)?;
+        ctx.accounts.donation.refunded = true;
+
+        Ok(())
+    }
+
+    pub fn sweep_surplus(ctx: Context<SweepSurplus>) -> Result<()> {
+        let project = &ctx.accounts.project;
+        let surplus = ctx
+            .accounts
+            .project_vault
+            .amount
+            .saturating_sub(project.accounted_balance()?);
+        require!(surplus > 0, FundingError::NoSurplus);
+
+        let project_id_bytes = project.project_id_seed;
+        let bump = [project.bump];
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Project::SEED_PREFIX,
+                project.creator.as_ref(),
+                &project_id_bytes,
+                &bump,
+            ]
+        ];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.project_vault.to_account_info(),
+                to: ctx.accounts.authority_token_account.to_account_info(),
+                authority: ctx.accounts.project.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, surplus)?;
+
+        Ok(())
+    }
+
+    pub fn close_project(ctx: Context<CloseProject>) -> Result<()> {
+        let project = &ctx.accounts.project;
+        require!(
+            project.is_settled(Clock::get()?.unix_timestamp)?,
+            FundingError::ProjectNotSettled
+        );
+        // Stray tokens have to be swept first; closing never burns value
+        require!(ctx.accounts.project_vault.amount == 0, FundingError::VaultNotEmpty);
+
+        let project_id_bytes = project.project_id_seed;
+        let bump = [project.bump];
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Project::SEED_PREFIX,
+                project.creator.as_ref(),
+                &project_id_bytes,
+                &bump,
+            ]
+        ];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.project_vault.to_account_info(),
+                destination: ctx.accounts.creator.to_account_info(),
+                authority: ctx.accounts.project.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::close_account(cpi_ctx)?;
+
+        // Always derived, so the project can't close around its split or badge config
+        let creator = ctx.accounts.creator.to_account_info();
+        close_if_open(&ctx.accounts.payout_split, &creator)?;
+        close_if_open(&ctx.accounts.badge_config, &creator)?;
+
+        Ok(())
+    }
+
+    pub fn close_payout_split(ctx: Context<ClosePayoutSplit>) -> Result<()> {
+        let project = &mut ctx.accounts.project;
+        let releasable = match project.status {
+            ProjectStatus::Active => false,
+            // The split is the only way funds leave a split project
+            ProjectStatus::Successful => project.is_settled(Clock::get()?.unix_timestamp)?,
+            ProjectStatus::Failed | ProjectStatus::Refunding => true,
+        };
+        require!(releasable, FundingError::RecordStillNeeded);
+        project.payout_split_enabled = false;
+
+        Ok(())
+    }
+
+    pub fn close_badge_config(ctx: Context<CloseBadgeConfig>) -> Result<()> {
+        require!(
+            ctx.accounts.project.records_releasable(Clock::get()?.unix_timestamp),
+            FundingError::RecordStillNeeded
+        );
+
+        Ok(())
+    }
+
+    pub fn close_reward_tier(ctx: Context<CloseRewardTier>) -> Result<()> {
+        if let Some(project) = load_open_project(&ctx.accounts.project)? {
+            require!(
+                project.records_releasable(Clock::get()?.unix_timestamp),
+                FundingError::RecordStillNeeded
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn close_update(ctx: Context<CloseUpdate>) -> Result<()> {
+        if let Some(project) = load_open_project(&ctx.accounts.project)? {
+            require!(
+                project.records_releasable(Clock::get()?.unix_timestamp),
+                FundingError::RecordStillNeeded
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn close_dispute(ctx: Context<CloseDispute>) -> Result<()> {
+        if let Some(project) = load_open_project(&ctx.accounts.project)? {
+            // Successful projects only release records once the dispute is no longer open
+            require!(
+                project.records_releasable(Clock::get()?.unix_timestamp),
+                FundingError::RecordStillNeeded
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn close_donation(ctx: Context<CloseDonation>) -> Result<()> {
+        let donation = &ctx.accounts.donation;
+        if let Some(project) = load_open_project(&ctx.accounts.project)? {
+            let releasable = match project.status {
+                // Unrefunded donors of a failed project must not lose their claim
+                ProjectStatus::Failed | ProjectStatus::Refunding => {
+                    donation.refunded || donation.amount == 0
+                }
+                _ => project.records_releasable(Clock::get()?.unix_timestamp),
+            };
+            require!(releasable, FundingError::RecordStillNeeded);
+        }
+
+        Ok(())
+    }
+
+    pub fn close_referral(ctx: Context<CloseReferral>) -> Result<()> {
+        let referral = &ctx.accounts.referral;
+        if let Some(project) = load_open_project(&ctx.accounts.project)? {
+            let releasable = match project.status {
+                ProjectStatus::Successful => {
+                    project.payouts_unlocked(Clock::get()?.unix_timestamp)
+                        && (referral.reward_claimed
+                            || apply_bps(referral.total_referred, project.referral_reward_bps)? == 0)
+                }
+                _ => project.records_releasable(Clock::get()?.unix_timestamp),
+            };
+            require!(releasable, FundingError::RecordStillNeeded);
+        }
+
+        Ok(())
+    }
+
+    pub fn claim_referral_reward(ctx: Context<ClaimReferralReward>) -> Result<()> {
+        let project = &ctx.accounts.project;
+        require!(
+            matches!(project.status, ProjectStatus::Successful),
+            FundingError::ProjectNotSuccessful
+        );
+        require!(
+            project.payouts_unlocked(Clock::get()?.unix_timestamp),
+            FundingError::PayoutsLocked
+        );
+
+        let referral = &ctx.accounts.referral;
+        require!(!referral.reward_claimed, FundingError::ReferralRewardClaimed);
+
+        let reward = apply_bps(referral.total_referred, project.referral_reward_bps)?;
+        require!(reward > 0, FundingError::NothingToClaim);
+
+        let project_id_bytes = project.project_id_seed;
+        let bump = [project.bump];
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                Project::SEED_PREFIX,
+                project.creator.as_ref(),
+                &project_id_bytes,
+                &bump,
+            ]
+        ];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.project_vault.to_account_info(),
+                to: ctx.accounts.referrer_token_account.to_account_info(),
+                authority: ctx.accounts.project.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, reward)?;
+
+        let project = &mut ctx.accounts.project;
+        project.referral_rewards_claimed = project
+            .referral_rewards_claimed
+            .checked_add(reward)
+            .ok_or(FundingError::MathOverflow)?;
+        ctx.accounts.referral.reward_claimed = true;
+
+        Ok(())
+    }
+}
+
+/// Books a pledge that has already reached the vault against the project and the
+/// donor's record, applying any early-bird multiplier active at `now`.
+#[allow(clippy::too_many_arguments)]
+fn credit_pledge(
+    project: &mut Project,
+    project_key: Pubkey,
+    donation: &mut Donation,
+    donor: Pubkey,
+    donation_bump: u8,
+    amount: u64,
+    referred: bool,
+    now: i64,
+) -> Result<()> {
+    let weighted_amount = apply_bps(amount, project.early_bird_multiplier_bps(now))?;
+
+    project.total_pledged = project
+        .total_pledged
+        .checked_add(amount)
+        .ok_or(FundingError::MathOverflow)?;
+    project.total_weighted = project
+        .total_weighted
+        .checked_add(weighted_amount)
+        .ok_or(FundingError::MathOverflow)?;
+    if referred {
+        project.total_referred = project
+            .total_referred
+            .checked_add(amount)
+            .ok_or(FundingError::MathOverflow)?;
+    }
+
+    if donation.project == Pubkey::default() {
+        donation.project = project_key;
+        donation.donor = donor;
+        donation.first_pledge_ts = now;
+        donation.bump = donation_bump;
+    }
+    donation.amount = donation
+        .amount
+        .checked_add(amount)
+        .ok_or(FundingError::MathOverflow)?;
+    donation.weighted_amount = donation
+        .weighted_amount
+        .checked_add(weighted_amount)
+        .ok_or(FundingError::MathOverflow)?;
+    donation.pledge_count = donation
+        .pledge_count
+        .checked_add(1)
+        .ok_or(FundingError::MathOverflow)?;
+    donation.last_pledge_ts = now;
+
+    Ok(())
+}
+
+/// Re-derives the donor's badge tier and rewrites the badge metadata to match the
+/// donation as it stands now.
+/// Optional donor badge accounts a pledge passes so an existing badge can be refreshed.
+struct DonorBadgeAccounts<'a, 'info> {
+    badge_config: Option<&'a Account<'info, BadgeConfig>>,
+    donor_badge_mint: Option<&'a Account<'info, Mint>>,
+    donor_badge_metadata: Option<&'a UncheckedAccount<'info>>,
+    token_metadata_program: Option<&'a UncheckedAccount<'info>>,
+}
+
+/// Refreshes the donor's badge after a pledge once one has been minted; until then
+/// the badge accounts may be left out.
+fn refresh_minted_badge<'info>(
+    project: &Account<'info, Project>,
+    donation: &mut Donation,
+    badge: DonorBadgeAccounts<'_, 'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    sysvar_instructions: Option<AccountInfo<'info>>,
+) -> Result<()> {
+    if !donation.badge_minted {
+        return Ok(());
+    }
+
+    let (
+        Some(badge_config),
+        Some(donor_badge_mint),
+        Some(donor_badge_metadata),
+        Some(token_metadata_program),
+        Some(sysvar_instructions),
+    ) = (
+        badge.badge_config,
+        badge.donor_badge_mint,
+        badge.donor_badge_metadata,
+        badge.token_metadata_program,
+        sysvar_instructions,
+    )
+    else {
+        return err!(FundingError::BadgeAccountsMissing);
+    };
+    require_keys_eq!(
+        donor_badge_metadata.key(),
+        MetadataAccount::find_pda(&donor_badge_mint.key()).0,
+        FundingError::MetadataAccountMismatch
+    );
+
+    update_badge_metadata(
+        project,
+        badge_config,
+        donation,
+        &donor_badge_mint.to_account_info(),
+        &donor_badge_metadata.to_account_info(),
+        payer,
+        system_program,
+        &sysvar_instructions,
+        &token_metadata_program.to_account_info(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_badge_metadata<'info>(
+    project: &Account<'info, Project>,
+    badge_config: &BadgeConfig,
+    donation: &mut Donation,
+    donor_badge_mint: &AccountInfo<'info>,
+    donor_badge_metadata: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    sysvar_instructions: &AccountInfo<'info>,
+    token_metadata_program: &AccountInfo<'info>,
+) -> Result<()> {
+    donation.badge_tier = badge_config.tier_for(donation.weighted_amount);
+    let (name, uri) = badge_config.metadata_for(donation);
+
+    let metadata_ix = mpl_token_metadata::instructions::UpdateV1 {
+        authority: project.key(),
+        delegate_record: None,
+        token: None,
+        mint: donor_badge_mint.key(),
+        metadata: donor_badge_metadata.key(),
+        edition: None,
+        payer: payer.key(),
+        system_program: system_program.key(),
+        sysvar_instructions: sysvar_instructions.key(),
+        authorization_rules_program: None,
+        authorization_rules: None,
+    }
+    .instruction(mpl_token_metadata::instructions::UpdateV1InstructionArgs {
+        new_update_authority: None,
+        data: Some(mpl_token_metadata::types::Data {
+            name,
+            symbol: badge_config.symbol.clone(),
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+        }),
+        primary_sale_happened: None,
+        is_mutable: None,
+        collection: mpl_token_metadata::types::CollectionToggle::None,
+        collection_details: mpl_token_metadata::types::CollectionDetailsToggle::None,
+        uses: mpl_token_metadata::types::UsesToggle::None,
+        rule_set: mpl_token_metadata::types::RuleSetToggle::None,
+        authorization_data: None,
+    });
+
+    let project_id_bytes = project.project_id_seed;
+    let bump = [project.bump];
+    let signer_seeds: &[&[&[u8]]] = &[
+        &[
+            Project::SEED_PREFIX,
+            project.creator.as_ref(),
+            &project_id_bytes,
+            &bump,
+        ]
+    ];
+
+    let account_infos = [
+        project.to_account_info(),
+        donor_badge_mint.clone(),
+        donor_badge_metadata.clone(),
+        payer.clone(),
+        system_program.clone(),
+        sysvar_instructions.clone(),
+        token_metadata_program.clone(),
+    ];
+
+    invoke_signed(&metadata_ix, &account_infos, signer_seeds)?;
+
+    Ok(())
+}
+
+/// Bytes a partner signs to authorize crediting `amount` to `donor` on `project`.
+pub fn voucher_message(project: &Pubkey, donor: &Pubkey, amount: u64, nonce: u64) -> [u8; 80] {
+    let mut message = [0u8; 80];
+    message[..32].copy_from_slice(project.as_ref());
+    message[32..64].copy_from_slice(donor.as_ref());
+    message[64..72].copy_from_slice(&amount.to_le_bytes());
+    message[72..].copy_from_slice(&nonce.to_le_bytes());
+    message
+}
+
+/// Checks that the instruction right before this one is an Ed25519 program
+/// instruction verifying `signer`'s signature over exactly `message`.
+fn verify_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, FundingError::MissingVoucherSignature);
+    let ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, FundingError::MissingVoucherSignature);
+
+    // Layout: [num_signatures, padding, 7 x u16 offsets, ...payload]
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, FundingError::InvalidVoucherSignature);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+
+    // Signature, key and message must all live in the Ed25519 instruction itself
+    require!(
+        read_u16(4) == u16::MAX && read_u16(8) == u16::MAX && read_u16(14) == u16::MAX,
+        FundingError::InvalidVoucherSignature
+    );
+
+    let public_key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(FundingError::InvalidVoucherSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(FundingError::InvalidVoucherSignature)?;
+
+    require!(
+        public_key == signer.as_ref() && signed_message == message,
+        FundingError::InvalidVoucherSignature
+    );
+
+    Ok(())
+}
+
+/// Deserializes a project that may already have been closed; `None` means it is gone.
+fn load_open_project(project_info: &AccountInfo) -> Result<Option<Project>> {
+    if project_info.owner != &crate::ID || project_info.data_is_empty() {
+        return Ok(None);
+    }
+    let data = project_info.try_borrow_data()?;
+    Ok(Some(Project::try_deserialize(&mut &data[..])?))
+}
+
+/// Closes a program-owned account if it exists; a missing one is left alone.
+fn close_if_open<'info>(info: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(());
+    }
+    let lamports = info.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(FundingError::MathOverflow)?;
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&system_program::ID);
+    info.realloc(0, false)?;
+    Ok(())
+}
+
+/// Adds a donor's weighted pledge to a dispute and freezes payouts once the
+/// platform threshold of the project's total weight is reached.
+fn back_dispute_with(
+    project: &mut Project,
+    dispute: &mut Dispute,
+    donation: &mut Donation,
+    threshold_bps: u16,
+) -> Result<()> {
+    require!(!donation.dispute_backed, FundingError::DisputeAlreadyBacked);
+    require!(donation.weighted_amount > 0, FundingError::NothingToClaim);
+
+    dispute.backing_weight = dispute
+        .backing_weight
+        .checked_add(donation.weighted_amount)
+        .ok_or(FundingError::MathOverflow)?;
+    dispute.backers = dispute
+        .backers
+        .checked_add(1)
+        .ok_or(FundingError::MathOverflow)?;
+    donation.dispute_backed = true;
+
+    let threshold = apply_bps(project.total_weighted, threshold_bps)?;
+    if project.dispute_status == DisputeStatus::Pending && dispute.backing_weight >= threshold {
+        project.dispute_status = DisputeStatus::Open;
+    }
+
+    Ok(())
+}
+
+/// Scales `amount` by a basis-point factor, e.g. an early-bird multiplier or a reward rate.
+fn apply_bps(amount: u64, bps: u16) -> Result<u64> {
+    let weighted = (amount as u128)
+        .checked_mul(bps as u128)
+        .and_then(|val| val.checked_div(BPS_DENOMINATOR as u128))
+        .ok_or(FundingError::MathOverflow)?;
+    u64::try_from(weighted).map_err(|_| error!(FundingError::MathOverflow))
+}
+
+pub const BPS_DENOMINATOR: u16 = 10_000;
+pub const MAX_EARLY_BIRD_WINDOWS: usize = 4;
+pub const MAX_EARLY_BIRD_MULTIPLIER_BPS: u16 = 30_000; // at most a 3x bonus
+pub const MAX_REFERRAL_REWARD_BPS: u16 = 2_000; // referrers never take more than 20%
+pub const MAX_BENEFICIARIES: usize = 8;
+pub const BADGE_TIER_COUNT: usize = 4;
+
+// Instruction families the guardian can pause independently via `Config.paused`
+pub const PAUSE_PROJECTS: u16 = 1 << 0;
+pub const PAUSE_PLEDGES: u16 = 1 << 1;
+pub const PAUSE_PAYOUTS: u16 = 1 << 2;
+pub const PAUSE_REFUNDS: u16 = 1 << 3;
+pub const PAUSE_DISPUTES: u16 = 1 << 4;
+pub const PAUSE_BADGES: u16 = 1 << 5;
+pub const PAUSE_CLOSING: u16 = 1 << 6;
+pub const PAUSE_ALL: u16 = PAUSE_PROJECTS
+    | PAUSE_PLEDGES
+    | PAUSE_PAYOUTS
+    | PAUSE_REFUNDS
+    | PAUSE_DISPUTES
+    | PAUSE_BADGES
+    | PAUSE_CLOSING;
+pub const BADGE_TIER_LABELS: [&str; BADGE_TIER_COUNT] = ["Backer", "Bronze", "Silver", "Gold"];
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = Config::LEN,
+        seeds = [Config::SEED_PREFIX],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, FundingHub>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ FundingError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ FundingError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ FundingError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        has_one = guardian @ FundingError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProject<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Legacy layout; owner checked here, discriminator and size in the handler
+    #[account(mut, owner = crate::ID @ FundingError::Unauthorized)]
+    pub project: UncheckedAccount<'info>,
+    pub project_vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(project_id: u64)]
+pub struct InitializeProject<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = Project::LEN,
+        seeds = [Project::SEED_PREFIX, authority.key().as_ref(), &project_id.to_le_bytes()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = project
+    )]
+    pub project_vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 0,
+        mint::authority = project,
+        mint::freeze_authority = project
+    )]
+    pub badge_mint: Account<'info, Mint>,
+    /// CHECK: Created via Metaplex CPI; address pinned to the mint's metadata PDA
+    #[account(
+        mut,
+        address = MetadataAccount::find_pda(&badge_mint.key()).0 @ FundingError::MetadataAccountMismatch
+    )]
+    pub badge_metadata: UncheckedAccount<'info>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// CHECK: Metaplex Token Metadata program
+    #[account(address = TOKEN_METADATA_ID @ FundingError::MetadataProgramMismatch)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: Sysvar Instructions
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pledge<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    #[account(
+        mut,
+        constraint = donor_token_account.owner == donor.key() @ FundingError::Unauthorized
+    )]
+    pub donor_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
+    )]
+    pub project_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = project.badge_mint @ FundingError::MintMismatch
+    )]
+    pub badge_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = donor,
+        associated_token::mint = badge_mint,
+        associated_token::authority = donor
+    )]
+    pub donor_badge_account: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = Donation::LEN,
+        seeds = [Donation::SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        mut,
+        seeds = [Referral::SEED_PREFIX, project.key().as_ref(), referral.referrer.as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, Referral>>,
+    #[account(
+        mut,
+        seeds = [RewardTier::SEED_PREFIX, project.key().as_ref(), &[reward_tier.tier_id]],
+        bump = reward_tier.bump
+    )]
+    pub reward_tier: Option<Account<'info, RewardTier>>,
+    // Donor badge accounts, required once the donor holds a badge so it tracks every pledge
+    #[account(
+        seeds = [BadgeConfig::SEED_PREFIX, project.key().as_ref()],
+        bump = badge_config.bump
+    )]
+    pub badge_config: Option<Account<'info, BadgeConfig>>,
+    #[account(
+        seeds = [DONOR_BADGE_SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub donor_badge_mint: Option<Account<'info, Mint>>,
+    /// CHECK: Updated via Metaplex CPI; checked against the donor badge mint's metadata PDA
+    #[account(mut)]
+    pub donor_badge_metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: Metaplex Token Metadata program
+    #[account(address = TOKEN_METADATA_ID @ FundingError::MetadataProgramMismatch)]
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Sysvar Instructions
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PLEDGES) @ FundingError::PledgesPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier_id: u8)]
+pub struct CreateRewardTier<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init,
+        payer = authority,
+        space = RewardTier::LEN,
+        seeds = [RewardTier::SEED_PREFIX, project.key().as_ref(), &[tier_id]],
+        bump
+    )]
+    pub reward_tier: Account<'info, RewardTier>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MarkRewardFulfilled<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        seeds = [RewardTier::SEED_PREFIX, project.key().as_ref(), &[reward_tier.tier_id]],
+        bump = reward_tier.bump
+    )]
+    pub reward_tier: Account<'info, RewardTier>,
+    #[account(
+        mut,
+        seeds = [Donation::SEED_PREFIX, project.key().as_ref(), donation.donor.as_ref()],
+        bump = donation.bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureDonorBadges<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = BadgeConfig::LEN,
+        seeds = [BadgeConfig::SEED_PREFIX, project.key().as_ref()],
+        bump
+    )]
+    pub badge_config: Account<'info, BadgeConfig>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_BADGES) @ FundingError::BadgesPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintDonorBadge<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    #[account(
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        seeds = [BadgeConfig::SEED_PREFIX, project.key().as_ref()],
+        bump = badge_config.bump
+    )]
+    pub badge_config: Account<'info, BadgeConfig>,
+    #[account(
+        mut,
+        has_one = donor @ FundingError::Unauthorized,
+        seeds = [Donation::SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump = donation.bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        init,
+        payer = donor,
+        seeds = [DONOR_BADGE_SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = project,
+        mint::freeze_authority = project
+    )]
+    pub donor_badge_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = donor,
+        associated_token::mint = donor_badge_mint,
+        associated_token::authority = donor
+    )]
+    pub donor_badge_token: Account<'info, TokenAccount>,
+    /// CHECK: Created via Metaplex CPI; address pinned to the mint's metadata PDA
+    #[account(
+        mut,
+        address = MetadataAccount::find_pda(&donor_badge_mint.key()).0 @ FundingError::MetadataAccountMismatch
+    )]
+    pub donor_badge_metadata: UncheckedAccount<'info>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_BADGES) @ FundingError::BadgesPaused
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: Metaplex Token Metadata program
+    #[account(address = TOKEN_METADATA_ID @ FundingError::MetadataProgramMismatch)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: Sysvar Instructions
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshDonorBadge<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        seeds = [BadgeConfig::SEED_PREFIX, project.key().as_ref()],
+        bump = badge_config.bump
+    )]
+    pub badge_config: Account<'info, BadgeConfig>,
+    #[account(
+        mut,
+        seeds = [Donation::SEED_PREFIX, project.key().as_ref(), donation.donor.as_ref()],
+        bump = donation.bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        seeds = [DONOR_BADGE_SEED_PREFIX, project.key().as_ref(), donation.donor.as_ref()],
+        bump
+    )]
+    pub donor_badge_mint: Account<'info, Mint>,
+    /// CHECK: Updated via Metaplex CPI; address pinned to the mint's metadata PDA
+    #[account(
+        mut,
+        address = MetadataAccount::find_pda(&donor_badge_mint.key()).0 @ FundingError::MetadataAccountMismatch
+    )]
+    pub donor_badge_metadata: UncheckedAccount<'info>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_BADGES) @ FundingError::BadgesPaused
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: Metaplex Token Metadata program
+    #[account(address = TOKEN_METADATA_ID @ FundingError::MetadataProgramMismatch)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Sysvar Instructions
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(signer: Pubkey)]
+pub struct RegisterPartner<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ FundingError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        space = Partner::LEN,
+        seeds = [Partner::SEED_PREFIX, signer.as_ref()],
+        bump
+    )]
+    pub partner: Account<'info, Partner>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPartnerActive<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ FundingError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [Partner::SEED_PREFIX, partner.signer.as_ref()],
+        bump = partner.bump
+    )]
+    pub partner: Account<'info, Partner>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, nonce: u64)]
+pub struct RedeemPledgeVoucher<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Wallet credited with the pledge; bound by the partner's signature
+    pub donor: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [Partner::SEED_PREFIX, partner.signer.as_ref()],
+        bump = partner.bump
+    )]
+    pub partner: Account<'info, Partner>,
+    #[account(
+        mut,
+        constraint = partner_funding_account.mint == project.mint @ FundingError::MintMismatch,
+        constraint = partner_funding_account.owner == partner.signer @ FundingError::Unauthorized,
+        constraint = partner_funding_account.delegate == COption::Some(partner.key()) @ FundingError::Unauthorized
+    )]
+    pub partner_funding_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
+    )]
+    pub project_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = project.badge_mint @ FundingError::MintMismatch
+    )]
+    pub badge_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = badge_mint,
+        associated_token::authority = donor
+    )]
+    pub donor_badge_account: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Donation::LEN,
+        seeds = [Donation::SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        init,
+        payer = payer,
+        space = VoucherNonce::LEN,
+        seeds = [VoucherNonce::SEED_PREFIX, partner.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub voucher_nonce: Account<'info, VoucherNonce>,
+    // Donor badge accounts, required once the donor holds a badge so it tracks every pledge
+    #[account(
+        seeds = [BadgeConfig::SEED_PREFIX, project.key().as_ref()],
+        bump = badge_config.bump
+    )]
+    pub badge_config: Option<Account<'info, BadgeConfig>>,
+    #[account(
+        seeds = [DONOR_BADGE_SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub donor_badge_mint: Option<Account<'info, Mint>>,
+    /// CHECK: Updated via Metaplex CPI; checked against the donor badge mint's metadata PDA
+    #[account(mut)]
+    pub donor_badge_metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: Metaplex Token Metadata program
+    #[account(address = TOKEN_METADATA_ID @ FundingError::MetadataProgramMismatch)]
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PLEDGES) @ FundingError::PledgesPaused
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: Sysvar Instructions
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SetEarlyBirdSchedule<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferralReward<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    #[account(
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init,
+        payer = referrer,
+        space = Referral::LEN,
+        seeds = [Referral::SEED_PREFIX, project.key().as_ref(), referrer.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PLEDGES) @ FundingError::PledgesPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetBeneficiaries<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = PayoutSplit::LEN,
+        seeds = [PayoutSplit::SEED_PREFIX, project.key().as_ref()],
+        bump
+    )]
+    pub payout_split: Account<'info, PayoutSplit>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub new_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct PostUpdate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init,
+        payer = authority,
+        space = ProjectUpdate::LEN,
+        seeds = [ProjectUpdate::SEED_PREFIX, project.key().as_ref(), &project.update_count.to_le_bytes()],
+        bump
+    )]
+    pub update: Account<'info, ProjectUpdate>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProject<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROJECTS) @ FundingError::ProjectsPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        has_one = donor @ FundingError::Unauthorized,
+        seeds = [Donation::SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump = donation.bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        init,
+        payer = donor,
+        space = Dispute::LEN,
+        seeds = [Dispute::SEED_PREFIX, project.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DISPUTES) @ FundingError::DisputesPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BackDispute<'info> {
+    pub donor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        has_one = donor @ FundingError::Unauthorized,
+        seeds = [Donation::SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump = donation.bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        mut,
+        seeds = [Dispute::SEED_PREFIX, project.key().as_ref()],
+        bump = dispute.bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DISPUTES) @ FundingError::DisputesPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    pub arbiter: Signer<'info>,
+    #[account(
+        has_one = arbiter @ FundingError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DISPUTES) @ FundingError::DisputesPaused
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        seeds = [Dispute::SEED_PREFIX, project.key().as_ref()],
+        bump = dispute.bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
+    )]
+    pub project_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = authority_token_account.mint == project.mint @ FundingError::MintMismatch,
+        constraint = authority_token_account.owner == authority.key()
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PAYOUTS) @ FundingError::PayoutsPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Distribute<'info> {
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        seeds = [PayoutSplit::SEED_PREFIX, project.key().as_ref()],
+        bump = payout_split.bump
+    )]
+    pub payout_split: Account<'info, PayoutSplit>,
+    #[account(
+        mut,
+        constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
+    )]
+    pub project_vault: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PAYOUTS) @ FundingError::PayoutsPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+    // remaining_accounts: one token account per beneficiary, in registration order
+}
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    pub donor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        has_one = donor @ FundingError::Unauthorized,
+        seeds = [Donation::SEED_PREFIX, project.key().as_ref(), donor.key().as_ref()],
+        bump = donation.bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        mut,
+        constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
+    )]
+    pub project_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = donor_token_account.mint == project.mint @ FundingError::MintMismatch,
+        constraint = donor_token_account.owner == donor.key() @ FundingError::Unauthorized
+    )]
+    pub donor_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_REFUNDS) @ FundingError::RefundsPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SweepSurplus<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
+    )]
+    pub project_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = authority_token_account.mint == project.mint @ FundingError::MintMismatch,
+        constraint = authority_token_account.owner == authority.key() @ FundingError::Unauthorized
+    )]
+    pub authority_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PAYOUTS) @ FundingError::PayoutsPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProject<'info> {
+    pub authority: Signer<'info>,
+    /// CHECK: Receives the rent; must be the creator who paid for the project
+    #[account(mut, address = project.creator @ FundingError::Unauthorized)]
+    pub creator: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = creator,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
+    )]
+    pub project_vault: Account<'info, TokenAccount>,
+    /// CHECK: The project's split PDA; closed to the creator if it was ever created
+    #[account(mut, seeds = [PayoutSplit::SEED_PREFIX, project.key().as_ref()], bump)]
+    pub payout_split: UncheckedAccount<'info>,
+    /// CHECK: The project's badge config PDA; closed to the creator if it was ever created
+    #[account(mut, seeds = [BadgeConfig::SEED_PREFIX, project.key().as_ref()], bump)]
+    pub badge_config: UncheckedAccount<'info>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLOSING) @ FundingError::ClosingPaused
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePayoutSplit<'info> {
+    pub authority: Signer<'info>,
+    /// CHECK: Receives the rent; must be the creator who paid for the project
+    #[account(mut, address = project.creator @ FundingError::Unauthorized)]
+    pub creator: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        close = creator,
+        seeds = [PayoutSplit::SEED_PREFIX, project.key().as_ref()],
+        bump = payout_split.bump
+    )]
+    pub payout_split: Account<'info, PayoutSplit>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLOSING) @ FundingError::ClosingPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBadgeConfig<'info> {
+    pub authority: Signer<'info>,
+    /// CHECK: Receives the rent; must be the creator who paid for the project
+    #[account(mut, address = project.creator @ FundingError::Unauthorized)]
+    pub creator: UncheckedAccount<'info>,
+    #[account(
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        close = creator,
+        seeds = [BadgeConfig::SEED_PREFIX, project.key().as_ref()],
+        bump = badge_config.bump
+    )]
+    pub badge_config: Account<'info, BadgeConfig>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLOSING) @ FundingError::ClosingPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRewardTier<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: May already be closed; only deserialized while it still exists
+    #[account(address = reward_tier.project @ FundingError::Unauthorized)]
+    pub project: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [RewardTier::SEED_PREFIX, reward_tier.project.as_ref(), &[reward_tier.tier_id]],
+        bump = reward_tier.bump
+    )]
+    pub reward_tier: Account<'info, RewardTier>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLOSING) @ FundingError::ClosingPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CloseUpdate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: May already be closed; only deserialized while it still exists
+    #[account(address = update.project @ FundingError::Unauthorized)]
+    pub project: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority @ FundingError::Unauthorized,
+        seeds = [ProjectUpdate::SEED_PREFIX, update.project.as_ref(), &update.index.to_le_bytes()],
+        bump = update.bump
+    )]
+    pub update: Account<'info, ProjectUpdate>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLOSING) @ FundingError::ClosingPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDispute<'info> {
+    #[account(mut, address = dispute.opened_by @ FundingError::Unauthorized)]
+    pub opened_by: Signer<'info>,
+    /// CHECK: May already be closed; only deserialized while it still exists
+    #[account(address = dispute.project @ FundingError::Unauthorized)]
+    pub project: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = opened_by,
+        seeds = [Dispute::SEED_PREFIX, dispute.project.as_ref()],
+        bump = dispute.bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLOSING) @ FundingError::ClosingPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDonation<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+    /// CHECK: May already be closed; only deserialized while it still exists
+    #[account(address = donation.project @ FundingError::Unauthorized)]
+    pub project: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = donor,
+        has_one = donor @ FundingError::Unauthorized,
+        seeds = [Donation::SEED_PREFIX, donation.project.as_ref(), donor.key().as_ref()],
+        bump = donation.bump
+    )]
+    pub donation: Account<'info, Donation>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLOSING) @ FundingError::ClosingPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReferral<'info> {
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    /// CHECK: May already be closed; only deserialized while it still exists
+    #[account(address = referral.project @ FundingError::Unauthorized)]
+    pub project: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = referrer,
+        has_one = referrer @ FundingError::Unauthorized,
+        seeds = [Referral::SEED_PREFIX, referral.project.as_ref(), referrer.key().as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Account<'info, Referral>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLOSING) @ FundingError::ClosingPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralReward<'info> {
+    pub referrer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [Project::SEED_PREFIX, project.creator.as_ref(), project.project_id_seed.as_ref()],
+        bump
     )]
     pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        has_one = referrer @ FundingError::Unauthorized,
+        seeds = [Referral::SEED_PREFIX, project.key().as_ref(), referrer.key().as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Account<'info, Referral>,
     #[account(
         mut,
         constraint = project_vault.key() == project.vault @ FundingError::VaultMismatch
@@ -346,13 +2774,29 @@ pub struct Withdraw<'info> {
     pub project_vault: Account<'info, TokenAccount>,
     #[account(
         mut,
-        constraint = authority_token_account.mint == project.mint @ FundingError::MintMismatch,
-        constraint = authority_token_account.owner == authority.key()
+        constraint = referrer_token_account.mint == project.mint @ FundingError::MintMismatch,
+        constraint = referrer_token_account.owner == referrer.key() @ FundingError::Unauthorized
     )]
-    pub authority_token_account: Account<'info, TokenAccount>,
+    pub referrer_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PAYOUTS) @ FundingError::PayoutsPaused
+    )]
+    pub config: Account<'info, Config>,
     pub token_program: Program<'info, Token>,
 }
 
+/// Crowdfunding campaign and the escrow accounting for its vault.
+///
+/// Every token that leaves the vault is attributed to one of `total_withdrawn`
+/// (creator or beneficiaries), `total_refunded` (donors of a failed project) or
+/// `referral_rewards_claimed`, so the vault should always hold exactly
+/// [`Project::accounted_balance`]. Anything above that was not pledged and can be
+/// moved out with `sweep_surplus` without touching donor funds.
+///
+/// The PDA is derived from `creator`, which never changes, so `authority` can be
+/// handed over through `propose_authority` / `accept_authority`.
 #[account]
 pub struct Project {
     pub project_id: u64,
@@ -367,6 +2811,22 @@ pub struct Project {
     pub status: ProjectStatus,
     pub bump: u8,
     pub reserved: [u8; 6],
+    pub total_weighted: u64,
+    pub early_bird_window_count: u8,
+    pub early_bird_windows: [EarlyBirdWindow; MAX_EARLY_BIRD_WINDOWS],
+    pub referral_reward_bps: u16,
+    pub total_referred: u64,
+    pub referral_rewards_claimed: u64,
+    pub payout_split_enabled: bool,
+    pub total_withdrawn: u64,
+    pub total_refunded: u64,
+    pub creator: Pubkey,
+    pub pending_authority: Pubkey,
+    pub update_count: u32,
+    pub dispute_window_end_ts: i64,
+    pub dispute_status: DisputeStatus,
+    pub referral_reward_pool: u64,
+    pub version: u8,
 }
 
 impl Project {
@@ -382,8 +2842,492 @@ impl Project {
         + 8 // total_pledged
         + 1 // status
         + 1 // bump
-        + 6; // reserved padding
+        + 6 // reserved padding
+        + 8 // total_weighted
+        + 1 // early_bird_window_count
+        + EarlyBirdWindow::LEN * MAX_EARLY_BIRD_WINDOWS // early_bird_windows
+        + 2 // referral_reward_bps
+        + 8 // total_referred
+        + 8 // referral_rewards_claimed
+        + 1 // payout_split_enabled
+        + 8 // total_withdrawn
+        + 8 // total_refunded
+        + 32 // creator
+        + 32 // pending_authority
+        + 4 // update_count
+        + 8 // dispute_window_end_ts
+        + 1 // dispute_status
+        + 8 // referral_reward_pool
+        + 1; // version
+    /// Layout written by `initialize_project`; accounts without a version byte are v1.
+    pub const VERSION: u8 = 2;
     pub const SEED_PREFIX: &'static [u8] = b"project";
+
+    /// Multiplier for a pledge made at `now`; pledges outside every window count 1:1.
+    pub fn early_bird_multiplier_bps(&self, now: i64) -> u16 {
+        self.early_bird_windows[..self.early_bird_window_count as usize]
+            .iter()
+            .find(|window| now < window.end_ts)
+            .map(|window| window.multiplier_bps)
+            .unwrap_or(BPS_DENOMINATOR)
+    }
+
+    /// Pledged tokens that have not yet left the vault through any tracked outflow.
+    pub fn accounted_balance(&self) -> Result<u64> {
+        let balance = self
+            .total_pledged
+            .checked_sub(self.total_withdrawn)
+            .and_then(|val| val.checked_sub(self.total_refunded))
+            .and_then(|val| val.checked_sub(self.referral_rewards_claimed))
+            .ok_or(FundingError::MathOverflow)?;
+        Ok(balance)
+    }
+
+    /// What the creator side may still take out: pledges minus the referral pool
+    /// minus everything already withdrawn or distributed.
+    pub fn withdrawable(&self) -> Result<u64> {
+        let withdrawable = self
+            .total_pledged
+            .checked_sub(self.referral_reward_pool)
+            .and_then(|val| val.checked_sub(self.total_withdrawn))
+            .ok_or(FundingError::MathOverflow)?;
+        Ok(withdrawable)
+    }
+
+    /// Funds may leave a successful project once its dispute window has passed,
+    /// unless donors opened a dispute that the arbiter has not resolved yet.
+    pub fn payouts_unlocked(&self, now: i64) -> bool {
+        now >= self.dispute_window_end_ts && self.dispute_status != DisputeStatus::Open
+    }
+
+    /// Whether every pledged token has reached its final destination: paid out
+    /// (including referral rewards) for a successful project, refunded otherwise.
+    pub fn is_settled(&self, now: i64) -> Result<bool> {
+        Ok(match self.status {
+            ProjectStatus::Active => false,
+            ProjectStatus::Successful => {
+                self.payouts_unlocked(now) && self.accounted_balance()? == 0
+            }
+            ProjectStatus::Failed | ProjectStatus::Refunding => {
+                self.total_refunded == self.total_pledged
+            }
+        })
+    }
+
+    /// Whether per-donor and per-referrer records are no longer needed for payouts,
+    /// refunds or disputes.
+    pub fn records_releasable(&self, now: i64) -> bool {
+        match self.status {
+            ProjectStatus::Active => false,
+            ProjectStatus::Successful => self.payouts_unlocked(now),
+            ProjectStatus::Failed | ProjectStatus::Refunding => true,
+        }
+    }
+}
+
+/// `Project` as laid out before accounts carried a version byte.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProjectV1 {
+    pub project_id: u64,
+    pub project_id_seed: [u8; 8],
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub badge_mint: Pubkey,
+    pub vault: Pubkey,
+    pub target_amount: u64,
+    pub deadline_ts: i64,
+    pub total_pledged: u64,
+    pub status: ProjectStatus,
+    pub bump: u8,
+    pub reserved: [u8; 6],
+}
+
+impl ProjectV1 {
+    pub const LEN: usize = 8 // discriminator
+        + 8 // project_id
+        + 8 // project_id_seed
+        + 32 // authority
+        + 32 // mint
+        + 32 // badge_mint
+        + 32 // vault
+        + 8 // target_amount
+        + 8 // deadline_ts
+        + 8 // total_pledged
+        + 1 // status
+        + 1 // bump
+        + 6; // reserved padding
+
+    /// Parses raw v1 account data, discriminator included.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 && data[..8] == Project::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    /// Upgrades to the current layout. v1 never recorded withdrawals, so for a
+    /// successful project whatever left the vault is booked as withdrawn.
+    pub fn migrate(self, vault_amount: u64) -> Project {
+        let total_withdrawn = if matches!(self.status, ProjectStatus::Successful) {
+            self.total_pledged.saturating_sub(vault_amount)
+        } else {
+            0
+        };
+
+        Project {
+            project_id: self.project_id,
+            project_id_seed: self.project_id_seed,
+            authority: self.authority,
+            mint: self.mint,
+            badge_mint: self.badge_mint,
+            vault: self.vault,
+            target_amount: self.target_amount,
+            deadline_ts: self.deadline_ts,
+            total_pledged: self.total_pledged,
+            status: self.status,
+            bump: self.bump,
+            reserved: self.reserved,
+            total_weighted: self.total_pledged,
+            early_bird_window_count: 0,
+            early_bird_windows: [EarlyBirdWindow::default(); MAX_EARLY_BIRD_WINDOWS],
+            referral_reward_bps: 0,
+            total_referred: 0,
+            referral_rewards_claimed: 0,
+            payout_split_enabled: false,
+            total_withdrawn,
+            total_refunded: 0,
+            creator: self.authority,
+            pending_authority: Pubkey::default(),
+            update_count: 0,
+            dispute_window_end_ts: 0,
+            dispute_status: DisputeStatus::None,
+            referral_reward_pool: 0,
+            version: Project::VERSION,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EarlyBirdWindow {
+    pub end_ts: i64,
+    pub multiplier_bps: u16,
+}
+
+impl EarlyBirdWindow {
+    pub const LEN: usize = 8 // end_ts
+        + 2; // multiplier_bps
+}
+
+/// Per-donor record of everything pledged to a project.
+///
+/// `amount` is the raw token amount and is what refunds pay back; `weighted_amount`
+/// includes early-bird bonuses and is meant for badge tiers, voting weight and rewards.
+#[account]
+pub struct Donation {
+    pub project: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+    pub weighted_amount: u64,
+    pub pledge_count: u32,
+    pub last_pledge_ts: i64,
+    pub bump: u8,
+    pub reserved: [u8; 16],
+    pub refunded: bool,
+    pub dispute_backed: bool,
+    pub first_pledge_ts: i64,
+    pub badge_tier: u8,
+    pub reward_tier: Option<u8>,
+    pub reward_fulfilled: bool,
+    /// Set once `mint_donor_badge` has run; later pledges then refresh the badge.
+    pub badge_minted: bool,
+}
+
+impl Donation {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // project
+        + 32 // donor
+        + 8 // amount
+        + 8 // weighted_amount
+        + 4 // pledge_count
+        + 8 // last_pledge_ts
+        + 1 // bump
+        + 16 // reserved
+        + 1 // refunded
+        + 1 // dispute_backed
+        + 8 // first_pledge_ts
+        + 1 // badge_tier
+        + 2 // reward_tier
+        + 1 // reward_fulfilled
+        + 1; // badge_minted
+    pub const SEED_PREFIX: &'static [u8] = b"donation";
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Beneficiary {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+}
+
+impl Beneficiary {
+    pub const LEN: usize = 32 // wallet
+        + 2; // share_bps
+}
+
+/// How a project's payout is split when `distribute` is called.
+#[account]
+pub struct PayoutSplit {
+    pub project: Pubkey,
+    pub beneficiaries: Vec<Beneficiary>,
+    pub bump: u8,
+}
+
+impl PayoutSplit {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // project
+        + 4 + Beneficiary::LEN * MAX_BENEFICIARIES // beneficiaries
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"payout_split";
+}
+
+/// Limited-supply perk that backers claim by pledging at least `price`.
+#[account]
+pub struct RewardTier {
+    pub project: Pubkey,
+    pub authority: Pubkey,
+    pub tier_id: u8,
+    pub price: u64,
+    pub total_supply: u32,
+    pub claimed: u32,
+    pub fulfilled: u32,
+    pub fulfilment_hash: [u8; 32],
+    pub bump: u8,
+}
+
+impl RewardTier {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // project
+        + 32 // authority
+        + 1 // tier_id
+        + 8 // price
+        + 4 // total_supply
+        + 4 // claimed
+        + 4 // fulfilled
+        + 32 // fulfilment_hash
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"reward_tier";
+}
+
+/// Seed prefix for the per-donor badge NFT mint, derived from `[project, donor]`.
+pub const DONOR_BADGE_SEED_PREFIX: &[u8] = b"donor_badge";
+
+/// Naming and tier rules for the per-donor badge NFTs of a project.
+///
+/// Badge metadata points at `base_uri` with the donor's tier, amounts and pledge
+/// dates as query parameters, so the off-chain renderer can serve matching attributes.
+#[account]
+pub struct BadgeConfig {
+    pub project: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub base_uri: String,
+    pub tier_thresholds: [u64; BADGE_TIER_COUNT - 1],
+    pub bump: u8,
+}
+
+impl BadgeConfig {
+    // Leaves room for the tier label in Metaplex's 32-byte name
+    pub const MAX_NAME_LEN: usize = 25;
+    pub const MAX_SYMBOL_LEN: usize = 10;
+    // Leaves room for the attribute query string in Metaplex's 200-byte URI
+    pub const MAX_BASE_URI_LEN: usize = 80;
+    pub const LEN: usize = 8 // discriminator
+        + 32 // project
+        + 4 + Self::MAX_NAME_LEN // name
+        + 4 + Self::MAX_SYMBOL_LEN // symbol
+        + 4 + Self::MAX_BASE_URI_LEN // base_uri
+        + 8 * (BADGE_TIER_COUNT - 1) // tier_thresholds
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"badge_config";
+
+    /// Tier reached by a weighted contribution; 0 until the first threshold.
+    pub fn tier_for(&self, weighted_amount: u64) -> u8 {
+        self.tier_thresholds
+            .iter()
+            .filter(|threshold| weighted_amount >= **threshold)
+            .count() as u8
+    }
+
+    /// Metadata name and URI describing `donation`'s current support.
+    pub fn metadata_for(&self, donation: &Donation) -> (String, String) {
+        let name = format!("{} {}", self.name, BADGE_TIER_LABELS[donation.badge_tier as usize]);
+        let uri = format!(
+            "{}?tier={}&amount={}&weighted={}&first={}&last={}",
+            self.base_uri,
+            donation.badge_tier,
+            donation.amount,
+            donation.weighted_amount,
+            donation.first_pledge_ts,
+            donation.last_pledge_ts
+        );
+        (name, uri)
+    }
+}
+
+/// Platform-wide settings: dispute arbitration and the guardian's emergency pause flags.
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub arbiter: Pubkey,
+    pub dispute_window_secs: i64,
+    pub dispute_threshold_bps: u16,
+    pub guardian: Pubkey,
+    pub paused: u16,
+    pub bump: u8,
+    pub reserved: [u8; 16],
+}
+
+impl Config {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // admin
+        + 32 // arbiter
+        + 8 // dispute_window_secs
+        + 2 // dispute_threshold_bps
+        + 32 // guardian
+        + 2 // paused
+        + 1 // bump
+        + 16; // reserved
+    pub const SEED_PREFIX: &'static [u8] = b"config";
+
+    pub fn is_paused(&self, family: u16) -> bool {
+        self.paused & family != 0
+    }
+}
+
+/// On-chain record of a donor dispute against a successful project and its outcome.
+#[account]
+pub struct Dispute {
+    pub project: Pubkey,
+    pub opened_by: Pubkey,
+    pub reason_hash: [u8; 32],
+    pub opened_ts: i64,
+    pub backing_weight: u64,
+    pub backers: u32,
+    pub resolution_hash: [u8; 32],
+    pub resolved_ts: i64,
+    pub bump: u8,
+}
+
+impl Dispute {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // project
+        + 32 // opened_by
+        + 32 // reason_hash
+        + 8 // opened_ts
+        + 8 // backing_weight
+        + 4 // backers
+        + 32 // resolution_hash
+        + 8 // resolved_ts
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"dispute";
+}
+
+/// Fiat-onramp or integration partner allowed to credit pledges with signed vouchers.
+#[account]
+pub struct Partner {
+    pub signer: Pubkey,
+    pub active: bool,
+    pub total_redeemed: u64,
+    pub bump: u8,
+    pub reserved: [u8; 16],
+}
+
+impl Partner {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // signer
+        + 1 // active
+        + 8 // total_redeemed
+        + 1 // bump
+        + 16; // reserved
+    pub const SEED_PREFIX: &'static [u8] = b"partner";
+}
+
+/// Marks a partner voucher nonce as spent so the same voucher can't be redeemed twice.
+/// Never closed: a project closed and re-created under the same seeds would accept
+/// the voucher again.
+#[account]
+pub struct VoucherNonce {
+    pub partner: Pubkey,
+    pub nonce: u64,
+    pub project: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+    pub redeemed_ts: i64,
+    pub bump: u8,
+}
+
+impl VoucherNonce {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // partner
+        + 8 // nonce
+        + 32 // project
+        + 32 // donor
+        + 8 // amount
+        + 8 // redeemed_ts
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"voucher";
+}
+
+/// One entry in a project's append-only update timeline.
+///
+/// Entries are addressed by `index`, so indexers can walk `0..project.update_count`
+/// and check each `content_hash` against the document served at `uri`.
+#[account]
+pub struct ProjectUpdate {
+    pub project: Pubkey,
+    pub authority: Pubkey,
+    pub index: u32,
+    pub content_hash: [u8; 32],
+    pub uri: String,
+    pub posted_ts: i64,
+    pub bump: u8,
+}
+
+impl ProjectUpdate {
+    pub const MAX_URI_LEN: usize = 200;
+    pub const LEN: usize = 8 // discriminator
+        + 32 // project
+        + 32 // authority
+        + 4 // index
+        + 32 // content_hash
+        + 4 + Self::MAX_URI_LEN // uri
+        + 8 // posted_ts
+        + 1; // bump
+    pub const SEED_PREFIX: &'static [u8] = b"update";
+}
+
+/// Running totals for pledges a referrer brought to a project.
+#[account]
+pub struct Referral {
+    pub project: Pubkey,
+    pub referrer: Pubkey,
+    pub total_referred: u64,
+    pub referred_pledges: u32,
+    pub reward_claimed: bool,
+    pub bump: u8,
+    pub reserved: [u8; 16],
+}
+
+impl Referral {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // project
+        + 32 // referrer
+        + 8 // total_referred
+        + 4 // referred_pledges
+        + 1 // reward_claimed
+        + 1 // bump
+        + 16; // reserved
+    pub const SEED_PREFIX: &'static [u8] = b"referral";
 }
 
 #[derive(
@@ -393,18 +3337,36 @@ impl Project {
     Copy,
     PartialEq,
     Eq,
+    Default,
 )]
 #[repr(u8)]
 pub enum ProjectStatus {
+    #[default]
     Active = 0,
     Successful = 1,
     Failed = 2,
+    /// Arbiter sided with donors after a dispute; donors reclaim pledges via `refund`.
+    Refunding = 3,
 }
 
-impl Default for ProjectStatus {
-    fn default() -> Self {
-        ProjectStatus::Active
-    }
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+)]
+#[repr(u8)]
+pub enum DisputeStatus {
+    #[default]
+    None = 0,
+    /// Opened but not yet backed by enough pledged weight to freeze payouts.
+    Pending = 1,
+    Open = 2,
+    Released = 3,
+    Refunded = 4,
 }
 
 #[error_code]
@@ -443,4 +3405,188 @@ pub enum FundingError {
     SymbolTooLong,
     #[msg("Badge URI too long (max 200 characters)")]
     UriTooLong,
+    #[msg("Too many early-bird windows")]
+    TooManyEarlyBirdWindows,
+    #[msg("Early-bird windows must be ordered and end before the deadline")]
+    InvalidEarlyBirdWindow,
+    #[msg("Early-bird multiplier out of range")]
+    InvalidEarlyBirdMultiplier,
+    #[msg("Project has already received pledges")]
+    PledgesAlreadyReceived,
+    #[msg("Referral reward out of range")]
+    InvalidReferralReward,
+    #[msg("Referral account does not match referrer")]
+    ReferralMismatch,
+    #[msg("Donors cannot refer themselves")]
+    SelfReferral,
+    #[msg("Referral reward already claimed")]
+    ReferralRewardClaimed,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Too many beneficiaries")]
+    TooManyBeneficiaries,
+    #[msg("Beneficiary shares must be non-zero and sum to 10,000 bps")]
+    InvalidBeneficiaryShares,
+    #[msg("Beneficiary listed more than once")]
+    DuplicateBeneficiary,
+    #[msg("Payouts go through distribute while a split is configured")]
+    PayoutSplitActive,
+    #[msg("No payout split configured")]
+    PayoutSplitNotConfigured,
+    #[msg("Beneficiary token accounts do not match the payout split")]
+    BeneficiaryAccountsMismatch,
+    #[msg("Withdrawal exceeds the creator's share of raised funds")]
+    WithdrawalExceedsRaised,
+    #[msg("Project is not refundable")]
+    ProjectNotRefundable,
+    #[msg("Donation already refunded")]
+    AlreadyRefunded,
+    #[msg("Vault holds no unaccounted surplus")]
+    NoSurplus,
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+    #[msg("Invalid dispute configuration")]
+    InvalidDisputeConfig,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("A dispute was already opened for this project")]
+    DisputeAlreadyOpened,
+    #[msg("No open dispute")]
+    NoOpenDispute,
+    #[msg("Donation already backs this dispute")]
+    DisputeAlreadyBacked,
+    #[msg("Payouts are locked by the dispute window or an open dispute")]
+    PayoutsLocked,
+    #[msg("Partner is not active")]
+    PartnerInactive,
+    #[msg("Voucher must be preceded by an Ed25519 signature instruction")]
+    MissingVoucherSignature,
+    #[msg("Voucher signature does not match the partner or voucher contents")]
+    InvalidVoucherSignature,
+    #[msg("Badge tier thresholds must be positive and strictly increasing")]
+    InvalidBadgeTiers,
+    #[msg("Metadata account does not match the mint's metadata PDA")]
+    MetadataAccountMismatch,
+    #[msg("Reward supply must be positive")]
+    InvalidRewardSupply,
+    #[msg("Pledge is below the reward tier price")]
+    PledgeBelowRewardPrice,
+    #[msg("Reward tier is sold out")]
+    RewardTierSoldOut,
+    #[msg("Donor already claimed a reward on this project")]
+    RewardAlreadyClaimed,
+    #[msg("Donor has not claimed this reward")]
+    NoRewardClaimed,
+    #[msg("Reward already fulfilled")]
+    RewardAlreadyFulfilled,
+    #[msg("Project still holds pledged funds or is not finished")]
+    ProjectNotSettled,
+    #[msg("Vault must be empty before closing")]
+    VaultNotEmpty,
+    #[msg("Record is still needed for payouts, refunds or disputes")]
+    RecordStillNeeded,
+    #[msg("Account is already on the current layout")]
+    AlreadyMigrated,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("Project management is paused")]
+    ProjectsPaused,
+    #[msg("Pledges are paused")]
+    PledgesPaused,
+    #[msg("Withdrawals and payouts are paused")]
+    PayoutsPaused,
+    #[msg("Refunds are paused")]
+    RefundsPaused,
+    #[msg("Disputes are paused")]
+    DisputesPaused,
+    #[msg("Badge minting is paused")]
+    BadgesPaused,
+    #[msg("Account closing is paused")]
+    ClosingPaused,
+    #[msg("Voucher amount exceeds the partner's delegated allowance")]
+    VoucherExceedsAllowance,
+    #[msg("Donor badge accounts are required once a badge has been minted")]
+    BadgeAccountsMissing,
+    #[msg("Account layout version is not supported")]
+    UnsupportedVersion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raw `Project` bytes exactly as the pre-versioning program wrote them.
+    fn project_v1_fixture(status: ProjectStatus) -> Vec<u8> {
+        let mut data = Vec::with_capacity(ProjectV1::LEN);
+        data.extend_from_slice(&Project::DISCRIMINATOR);
+        data.extend_from_slice(&7u64.to_le_bytes()); // project_id
+        data.extend_from_slice(&7u64.to_le_bytes()); // project_id_seed
+        data.extend_from_slice(&[1u8; 32]); // authority
+        data.extend_from_slice(&[2u8; 32]); // mint
+        data.extend_from_slice(&[3u8; 32]); // badge_mint
+        data.extend_from_slice(&[4u8; 32]); // vault
+        data.extend_from_slice(&1_000u64.to_le_bytes()); // target_amount
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes()); // deadline_ts
+        data.extend_from_slice(&1_500u64.to_le_bytes()); // total_pledged
+        data.push(status as u8);
+        data.push(254); // bump
+        data.extend_from_slice(&[0u8; 6]); // reserved
+        data
+    }
+
+    fn round_trip(project: &Project) -> Project {
+        let mut data = vec![0u8; Project::LEN];
+        let mut writer: &mut [u8] = &mut data;
+        project.try_serialize(&mut writer).unwrap();
+        Project::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    #[test]
+    fn deserializes_v1_project_fixture() {
+        let data = project_v1_fixture(ProjectStatus::Active);
+        assert_eq!(data.len(), ProjectV1::LEN);
+
+        let legacy = ProjectV1::try_from_bytes(&data).unwrap();
+        assert_eq!(legacy.project_id, 7);
+        assert_eq!(legacy.authority, Pubkey::new_from_array([1u8; 32]));
+        assert_eq!(legacy.vault, Pubkey::new_from_array([4u8; 32]));
+        assert_eq!(legacy.total_pledged, 1_500);
+        assert!(legacy.status == ProjectStatus::Active);
+        assert_eq!(legacy.bump, 254);
+    }
+
+    #[test]
+    fn migrates_successful_v1_project() {
+        let data = project_v1_fixture(ProjectStatus::Successful);
+        let project = ProjectV1::try_from_bytes(&data).unwrap().migrate(500);
+
+        let project = round_trip(&project);
+        assert_eq!(project.version, Project::VERSION);
+        assert_eq!(project.creator, project.authority);
+        assert_eq!(project.pending_authority, Pubkey::default());
+        assert_eq!(project.total_weighted, 1_500);
+        assert_eq!(project.total_withdrawn, 1_000);
+        assert_eq!(project.accounted_balance().unwrap(), 500);
+        assert_eq!(project.withdrawable().unwrap(), 500);
+        assert!(project.payouts_unlocked(0));
+    }
+
+    #[test]
+    fn migrates_failed_v1_project_without_withdrawals() {
+        let data = project_v1_fixture(ProjectStatus::Failed);
+        let project = ProjectV1::try_from_bytes(&data).unwrap().migrate(1_500);
+
+        let project = round_trip(&project);
+        assert_eq!(project.total_withdrawn, 0);
+        assert_eq!(project.total_refunded, 0);
+        assert_eq!(project.accounted_balance().unwrap(), 1_500);
+    }
+
+    #[test]
+    fn rejects_fixture_with_foreign_discriminator() {
+        let mut data = project_v1_fixture(ProjectStatus::Active);
+        data[..8].copy_from_slice(&Donation::DISCRIMINATOR);
+
+        assert!(ProjectV1::try_from_bytes(&data).is_err());
+    }
 }
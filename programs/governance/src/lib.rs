@@ -1,4 +1,8 @@
+use account_migration::{stored_version, write_migrated_account};
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use dao_pass::{Member, ROLE_ALL};
+use program::Governance;
 
 declare_id!("6pCiN5ZUf5GCY3hJ8YiWL27apECaobGPLVVsSi51rrUq");
 
@@ -6,6 +10,49 @@ declare_id!("6pCiN5ZUf5GCY3hJ8YiWL27apECaobGPLVVsSi51rrUq");
 pub mod governance {
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>, guardian: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.guardian = guardian;
+        config.paused = 0;
+        config.bump = ctx.bumps.config;
+
+        Ok(())
+    }
+
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.config.guardian = guardian;
+
+        Ok(())
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, paused: u16) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, GovernanceError::InvalidPauseFlags);
+        ctx.accounts.config.paused = paused;
+
+        Ok(())
+    }
+
+    pub fn migrate_realm(ctx: Context<MigrateRealm>) -> Result<()> {
+        let realm_info = ctx.accounts.realm.to_account_info();
+        let legacy = {
+            let data = realm_info.try_borrow_data()?;
+            match stored_version(&data, RealmV1::LEN, |realm: &Realm| realm.version)? {
+                1 => RealmV1::try_from_bytes(&data)?,
+                Realm::VERSION => return err!(GovernanceError::AlreadyMigrated),
+                _ => return err!(GovernanceError::UnsupportedVersion),
+            }
+        };
+
+        write_migrated_account(
+            &realm_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Realm::LEN,
+            &legacy.migrate(),
+        )
+    }
+
     pub fn create_realm(
         ctx: Context<CreateRealm>,
         name_seed: [u8; 8],
@@ -29,6 +76,21 @@ pub mod governance {
         realm.dao_address = dao_address.unwrap_or(Pubkey::default());
         realm.bump = ctx.bumps.realm;
         realm.proposal_count = 0;
+        realm.version = Realm::VERSION;
+
+        Ok(())
+    }
+
+    /// Restricts proposal creation to DAO members holding any of `roles`
+    /// (`dao_pass::ROLE_*` bits); zero lets anyone propose.
+    pub fn set_proposer_roles(ctx: Context<SetProposerRoles>, roles: u8) -> Result<()> {
+        require!(roles & !ROLE_ALL == 0, GovernanceError::InvalidRoles);
+        let realm = &mut ctx.accounts.realm;
+        if roles != 0 {
+            require!(realm.dao_address != Pubkey::default(), GovernanceError::RealmNotLinked);
+        }
+
+        realm.proposer_roles = roles;
 
         Ok(())
     }
@@ -44,6 +106,24 @@ pub mod governance {
 
         let realm = &mut ctx.accounts.realm;
 
+        if realm.proposer_roles != 0 {
+            let member = ctx
+                .accounts
+                .member
+                .as_ref()
+                .ok_or(GovernanceError::NotDaoMember)?;
+            require_keys_eq!(member.dao, realm.dao_address, GovernanceError::NotDaoMember);
+            require_keys_eq!(member.owner, ctx.accounts.proposer.key(), GovernanceError::NotDaoMember);
+            require!(
+                member.is_active(clock.unix_timestamp),
+                GovernanceError::MembershipExpired
+            );
+            require!(
+                member.has_any_role(realm.proposer_roles),
+                GovernanceError::MissingProposerRole
+            );
+        }
+
         let proposal = &mut ctx.accounts.proposal;
         proposal.realm = realm.key();
         proposal.proposer = ctx.accounts.proposer.key();
@@ -57,6 +137,7 @@ pub mod governance {
         proposal.yes_votes = 0;
         proposal.no_votes = 0;
         proposal.bump = ctx.bumps.proposal;
+        proposal.version = Proposal::VERSION;
 
         realm.proposal_count = realm
             .proposal_count
@@ -77,10 +158,24 @@ pub mod governance {
         require!(clock.slot >= proposal.voting_start_slot, GovernanceError::VotingNotOpen);
         require!(clock.slot <= proposal.voting_end_slot, GovernanceError::VotingClosed);
 
-        // If realm is linked to a DAO, verify voter has DAO membership
-        // This can be checked by verifying the voter holds the DAO's pass NFT
-        // For now, we'll allow the weight parameter to represent voting power
-        // In production, this should be verified against token holdings
+        // Realms linked to a DAO only accept votes from its active members
+        if realm.dao_address != Pubkey::default() {
+            let member = ctx
+                .accounts
+                .member
+                .as_ref()
+                .ok_or(GovernanceError::NotDaoMember)?;
+            require_keys_eq!(member.dao, realm.dao_address, GovernanceError::NotDaoMember);
+            require_keys_eq!(member.owner, ctx.accounts.voter.key(), GovernanceError::NotDaoMember);
+            require!(!member.revoked, GovernanceError::MemberRevoked);
+            require!(
+                member.is_active(clock.unix_timestamp),
+                GovernanceError::MembershipExpired
+            );
+        }
+
+        // The weight parameter still stands in for voting power; it is not
+        // yet verified against token holdings
 
         let vote_record = &mut ctx.accounts.vote_record;
         require!(vote_record.weight == 0, GovernanceError::AlreadyVoted);
@@ -90,6 +185,7 @@ pub mod governance {
         vote_record.support = support;
         vote_record.weight = weight;
         vote_record.bump = ctx.bumps.vote_record;
+        vote_record.version = VoteRecord::VERSION;
 
         if support {
             proposal.yes_votes = proposal
@@ -144,6 +240,65 @@ pub mod governance {
     }
 }
 
+// Instruction families the guardian can pause independently via `Config.paused`
+pub const PAUSE_REALMS: u16 = 1 << 0;
+pub const PAUSE_PROPOSALS: u16 = 1 << 1;
+pub const PAUSE_VOTES: u16 = 1 << 2;
+pub const PAUSE_ALL: u16 = PAUSE_REALMS | PAUSE_PROPOSALS | PAUSE_VOTES;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = Config::LEN,
+        seeds = [Config::SEED_PREFIX],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Governance>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ GovernanceError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ GovernanceError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        has_one = guardian @ GovernanceError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateRealm<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Legacy layout; owner checked here, discriminator and size in the handler
+    #[account(mut, owner = crate::ID @ GovernanceError::Unauthorized)]
+    pub realm: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(name_seed: [u8; 8])]
 pub struct CreateRealm<'info> {
@@ -157,9 +312,33 @@ pub struct CreateRealm<'info> {
         bump
     )]
     pub realm: Account<'info, Realm>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_REALMS) @ GovernanceError::RealmsPaused
+    )]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetProposerRoles<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ GovernanceError::Unauthorized,
+        seeds = [Realm::SEED_PREFIX, realm.authority.as_ref(), &realm.name_seed],
+        bump = realm.bump
+    )]
+    pub realm: Account<'info, Realm>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_REALMS) @ GovernanceError::RealmsPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
 #[derive(Accounts)]
 #[instruction(proposal_id: u64, _metadata_uri: Pubkey, _voting_start_slot: u64)]
 pub struct CreateProposal<'info> {
@@ -181,6 +360,14 @@ pub struct CreateProposal<'info> {
         bump
     )]
     pub proposal: Account<'info, Proposal>,
+    /// The proposer's dao_pass membership; required when the realm restricts proposer roles
+    pub member: Option<Account<'info, Member>>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROPOSALS) @ GovernanceError::ProposalsPaused
+    )]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
 
@@ -208,6 +395,14 @@ pub struct CastVote<'info> {
         bump
     )]
     pub vote_record: Account<'info, VoteRecord>,
+    /// The voter's dao_pass membership; required when the realm is linked to a DAO
+    pub member: Option<Account<'info, Member>>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_VOTES) @ GovernanceError::VotesPaused
+    )]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
 }
 
@@ -227,6 +422,12 @@ pub struct FinalizeProposal<'info> {
         bump = proposal.bump
     )]
     pub proposal: Account<'info, Proposal>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_PROPOSALS) @ GovernanceError::ProposalsPaused
+    )]
+    pub config: Account<'info, Config>,
 }
 
 #[account]
@@ -240,7 +441,10 @@ pub struct Realm {
     pub voting_period_slots: u64,
     pub proposal_count: u64,
     pub bump: u8,
-    pub reserved: [u8; 7],
+    /// `dao_pass::ROLE_*` bits allowed to propose; zero means anyone.
+    pub proposer_roles: u8,
+    pub reserved: [u8; 6],
+    pub version: u8,
 }
 
 impl Realm {
@@ -254,9 +458,68 @@ impl Realm {
         + 8  // voting_period_slots
         + 8  // proposal_count
         + 1  // bump
-        + 7; // reserved
+        + 1  // proposer_roles
+        + 6  // reserved
+        + 1; // version
     pub const SEED_PREFIX: &'static [u8] = b"realm";
+    /// Layout written by `create_realm`; accounts without a version byte are v1.
+    /// `proposer_roles` took a reserved byte that was always zero, so it needed no bump.
+    pub const VERSION: u8 = 2;
+}
 
+/// `Realm` as laid out before accounts carried a version byte.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RealmV1 {
+    pub authority: Pubkey,
+    pub voting_mint: Pubkey,
+    pub dao_address: Pubkey,
+    pub name_seed: [u8; 8],
+    pub min_quorum: u64,
+    pub approval_threshold: u64,
+    pub voting_period_slots: u64,
+    pub proposal_count: u64,
+    pub bump: u8,
+    pub reserved: [u8; 7],
+}
+
+impl RealmV1 {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // authority
+        + 32 // voting_mint
+        + 32 // dao_address
+        + 8  // name_seed
+        + 8  // min_quorum
+        + 8  // approval_threshold
+        + 8  // voting_period_slots
+        + 8  // proposal_count
+        + 1  // bump
+        + 7; // reserved
+
+    /// Parses raw v1 account data, discriminator included.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 && data[..8] == Realm::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    pub fn migrate(self) -> Realm {
+        Realm {
+            authority: self.authority,
+            voting_mint: self.voting_mint,
+            dao_address: self.dao_address,
+            name_seed: self.name_seed,
+            min_quorum: self.min_quorum,
+            approval_threshold: self.approval_threshold,
+            voting_period_slots: self.voting_period_slots,
+            proposal_count: self.proposal_count,
+            bump: self.bump,
+            proposer_roles: 0,
+            reserved: [0; 6],
+            version: Realm::VERSION,
+        }
+    }
 }
 
 #[account]
@@ -272,21 +535,27 @@ pub struct Proposal {
     pub status: ProposalStatus,
     pub bump: u8,
     pub reserved: [u8; 7],
+    pub version: u8,
 }
 
 impl Proposal {
-    pub const LEN: usize = 8
-        + 32
-        + 32
-        + 32
-        + 8
-        + 8
-        + 8
-        + 8
-        + 8
-        + 1
-        + 7;
+    // The pre-versioning LEN left out the bump byte, so `create_proposal` could
+    // never serialize a proposal and there are no v1 accounts to migrate.
+    pub const LEN: usize = 8 // discriminator
+        + 32 // realm
+        + 32 // proposer
+        + 32 // metadata_uri
+        + 8  // proposal_id
+        + 8  // voting_start_slot
+        + 8  // voting_end_slot
+        + 8  // yes_votes
+        + 8  // no_votes
+        + 1  // status
+        + 1  // bump
+        + 7  // reserved
+        + 1; // version
     pub const SEED_PREFIX: &'static [u8] = b"proposal";
+    pub const VERSION: u8 = 2;
 }
 
 #[account]
@@ -297,11 +566,14 @@ pub struct VoteRecord {
     pub weight: u64,
     pub bump: u8,
     pub reserved: [u8; 7],
+    pub version: u8,
 }
 
 impl VoteRecord {
-    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 1 + 7;
+    // Votes need a proposal, so like `Proposal` there are no v1 records.
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 1 + 7 + 1;
     pub const SEED_PREFIX: &'static [u8] = b"vote";
+    pub const VERSION: u8 = 2;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -312,6 +584,30 @@ pub enum ProposalStatus {
     Defeated = 2,
 }
 
+/// Program-wide emergency controls; the guardian flips `paused` bits per instruction family.
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    pub paused: u16,
+    pub bump: u8,
+    pub reserved: [u8; 16],
+}
+
+impl Config {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // admin
+        + 32 // guardian
+        + 2 // paused
+        + 1 // bump
+        + 16; // reserved
+    pub const SEED_PREFIX: &'static [u8] = b"config";
+
+    pub fn is_paused(&self, family: u16) -> bool {
+        self.paused & family != 0
+    }
+}
+
 #[error_code]
 pub enum GovernanceError {
     #[msg("Unauthorized")]
@@ -342,4 +638,94 @@ pub enum GovernanceError {
     RealmMismatch,
     #[msg("Bump missing")]
     BumpNotFound,
+    #[msg("Account is already on the current layout")]
+    AlreadyMigrated,
+    #[msg("Voter is not a member of the realm's DAO")]
+    NotDaoMember,
+    #[msg("Voter's DAO membership has been revoked")]
+    MemberRevoked,
+    #[msg("Voter's DAO membership has expired")]
+    MembershipExpired,
+    #[msg("Invalid role bits")]
+    InvalidRoles,
+    #[msg("Realm is not linked to a DAO")]
+    RealmNotLinked,
+    #[msg("Proposer does not hold a role allowed to propose")]
+    MissingProposerRole,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("Realm creation is paused")]
+    RealmsPaused,
+    #[msg("Proposals are paused")]
+    ProposalsPaused,
+    #[msg("Voting is paused")]
+    VotesPaused,
+    #[msg("Account layout version is not supported")]
+    UnsupportedVersion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raw `Realm` bytes exactly as the pre-versioning program wrote them.
+    fn realm_v1_fixture() -> Vec<u8> {
+        let mut data = Vec::with_capacity(RealmV1::LEN);
+        data.extend_from_slice(&Realm::DISCRIMINATOR);
+        data.extend_from_slice(&[1u8; 32]); // authority
+        data.extend_from_slice(&[2u8; 32]); // voting_mint
+        data.extend_from_slice(&[0u8; 32]); // dao_address
+        data.extend_from_slice(b"council!"); // name_seed
+        data.extend_from_slice(&2_000u64.to_le_bytes()); // min_quorum
+        data.extend_from_slice(&6_000u64.to_le_bytes()); // approval_threshold
+        data.extend_from_slice(&432_000u64.to_le_bytes()); // voting_period_slots
+        data.extend_from_slice(&0u64.to_le_bytes()); // proposal_count
+        data.push(254); // bump
+        data.extend_from_slice(&[0u8; 7]); // reserved
+        data
+    }
+
+    #[test]
+    fn migrates_v1_realm_fixture() {
+        let data = realm_v1_fixture();
+        assert_eq!(data.len(), RealmV1::LEN);
+
+        let realm = RealmV1::try_from_bytes(&data).unwrap().migrate();
+        let mut migrated = vec![0u8; Realm::LEN];
+        let mut writer: &mut [u8] = &mut migrated;
+        realm.try_serialize(&mut writer).unwrap();
+        let realm = Realm::try_deserialize(&mut &migrated[..]).unwrap();
+
+        assert_eq!(realm.version, Realm::VERSION);
+        assert_eq!(realm.authority, Pubkey::new_from_array([1u8; 32]));
+        assert_eq!(realm.dao_address, Pubkey::default());
+        assert_eq!(&realm.name_seed, b"council!");
+        assert_eq!(realm.min_quorum, 2_000);
+        assert_eq!(realm.approval_threshold, 6_000);
+        assert_eq!(realm.voting_period_slots, 432_000);
+        assert_eq!(realm.bump, 254);
+        assert_eq!(realm.proposer_roles, 0);
+    }
+
+    #[test]
+    fn proposal_len_fits_serialized_proposal() {
+        let proposal = Proposal {
+            realm: Pubkey::default(),
+            proposer: Pubkey::default(),
+            metadata_uri: Pubkey::default(),
+            proposal_id: 0,
+            voting_start_slot: 0,
+            voting_end_slot: 0,
+            yes_votes: 0,
+            no_votes: 0,
+            status: ProposalStatus::Pending,
+            bump: 0,
+            reserved: [0; 7],
+            version: Proposal::VERSION,
+        };
+        let mut data = Vec::new();
+        proposal.try_serialize(&mut data).unwrap();
+
+        assert_eq!(data.len(), Proposal::LEN);
+    }
 }
@@ -1,6 +1,11 @@
+use account_migration::{stored_version, write_migrated_account};
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use funding_hub::program::FundingHub;
+use funding_hub::{Project, ProjectStatus};
+use program::SavingsVault;
 
 declare_id!("HSnBJMRi1uipcnGeSRcM7kCD1DegertD93CgtKmn18tN");
 
@@ -8,6 +13,29 @@ declare_id!("HSnBJMRi1uipcnGeSRcM7kCD1DegertD93CgtKmn18tN");
 pub mod savings_vault {
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>, guardian: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.guardian = guardian;
+        config.paused = 0;
+        config.bump = ctx.bumps.config;
+
+        Ok(())
+    }
+
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.config.guardian = guardian;
+
+        Ok(())
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, paused: u16) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, VaultError::InvalidPauseFlags);
+        ctx.accounts.config.paused = paused;
+
+        Ok(())
+    }
+
     pub fn initialize_vault(
         ctx: Context<InitializeVault>,
         vault_id: [u8; 8],
@@ -28,10 +56,31 @@ pub mod savings_vault {
         vault.apy_bps = apy_bps;
         vault.total_deposited = 0;
         vault.bump = ctx.bumps.vault;
+        vault.version = Vault::VERSION;
 
         Ok(())
     }
 
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        let vault_info = ctx.accounts.vault.to_account_info();
+        let legacy = {
+            let data = vault_info.try_borrow_data()?;
+            match stored_version(&data, VaultV1::LEN, |vault: &Vault| vault.version)? {
+                1 => VaultV1::try_from_bytes(&data)?,
+                Vault::VERSION => return err!(VaultError::AlreadyMigrated),
+                _ => return err!(VaultError::UnsupportedVersion),
+            }
+        };
+
+        write_migrated_account(
+            &vault_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Vault::LEN,
+            &legacy.migrate(),
+        )
+    }
+
     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
         require!(amount > 0, VaultError::InvalidAmount);
 
@@ -61,6 +110,10 @@ pub mod savings_vault {
             .slot
             .checked_add(vault.term_slots)
             .ok_or(VaultError::MathOverflow)?;
+        // A claimed receipt starts over; its old target may long be gone
+        if deposit.claimed {
+            deposit.yield_target = Pubkey::default();
+        }
         deposit.claimed = false;
 
         let vault_mut = &mut ctx.accounts.vault;
@@ -70,16 +123,104 @@ pub mod savings_vault {
             .ok_or(VaultError::MathOverflow)?;
 
         deposit.bump = ctx.bumps.deposit;
+        deposit.version = DepositReceipt::VERSION;
 
         Ok(())
     }
 
-    pub fn claim(ctx: Context<Claim>) -> Result<()> {
-        let vault = &ctx.accounts.vault;
+    /// Points the receipt's yield at a funding_hub project, or clears it when no
+    /// project is passed.
+    pub fn set_yield_target(ctx: Context<SetYieldTarget>) -> Result<()> {
         let deposit = &mut ctx.accounts.deposit;
+        require!(!deposit.claimed, VaultError::AlreadyClaimed);
+
+        deposit.yield_target = ctx
+            .accounts
+            .project
+            .as_ref()
+            .map(|project| project.key())
+            .unwrap_or_default();
+
+        Ok(())
+    }
+
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        require!(
+            ctx.accounts.deposit.yield_target == Pubkey::default(),
+            VaultError::YieldTargetAssigned
+        );
+
+        ctx.accounts.settle()?;
+
+        Ok(())
+    }
+
+    /// Claims like `claim`, then pledges the reward to the receipt's yield target
+    /// with the saver as donor, so the badge and donation record land with them.
+    /// If the target no longer takes pledges the reward stays with the saver.
+    pub fn claim_and_pledge(ctx: Context<ClaimAndPledge>) -> Result<()> {
+        let yield_target = ctx.accounts.claim.deposit.yield_target;
+        require!(yield_target != Pubkey::default(), VaultError::NoYieldTarget);
+        require_keys_eq!(
+            ctx.accounts.project.key(),
+            yield_target,
+            VaultError::YieldTargetMismatch
+        );
+
+        let reward = ctx.accounts.claim.settle()?;
+        // A project that no longer takes pledges leaves the reward with the saver
+        let now = Clock::get()?.unix_timestamp;
+        if reward == 0 || !accepts_pledges(&ctx.accounts.project, now)? {
+            return Ok(());
+        }
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.funding_hub_program.to_account_info(),
+            funding_hub::cpi::accounts::Pledge {
+                donor: ctx.accounts.claim.user.to_account_info(),
+                donor_token_account: ctx.accounts.claim.user_reward_account.to_account_info(),
+                project: ctx.accounts.project.to_account_info(),
+                project_vault: ctx.accounts.project_vault.to_account_info(),
+                badge_mint: ctx.accounts.badge_mint.to_account_info(),
+                donor_badge_account: ctx.accounts.donor_badge_account.to_account_info(),
+                donation: ctx.accounts.donation.to_account_info(),
+                referral: None,
+                reward_tier: None,
+                badge_config: ctx.accounts.badge_config.as_ref().map(|a| a.to_account_info()),
+                donor_badge_mint: ctx.accounts.donor_badge_mint.as_ref().map(|a| a.to_account_info()),
+                donor_badge_metadata: ctx
+                    .accounts
+                    .donor_badge_metadata
+                    .as_ref()
+                    .map(|a| a.to_account_info()),
+                token_metadata_program: ctx
+                    .accounts
+                    .token_metadata_program
+                    .as_ref()
+                    .map(|a| a.to_account_info()),
+                sysvar_instructions: ctx.accounts.sysvar_instructions.as_ref().map(|a| a.to_account_info()),
+                config: ctx.accounts.funding_config.to_account_info(),
+                token_program: ctx.accounts.claim.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            },
+        );
+        funding_hub::cpi::pledge(cpi_ctx, reward, None)?;
+
+        Ok(())
+    }
+}
+
+impl<'info> Claim<'info> {
+    /// Pays out principal and reward to the saver and marks the receipt claimed.
+    /// Returns the reward so callers can route it onwards.
+    fn settle(&mut self) -> Result<u64> {
+        let vault = &self.vault;
+        let deposit = &mut self.deposit;
 
         require!(!deposit.claimed, VaultError::AlreadyClaimed);
-        require_keys_eq!(deposit.owner, ctx.accounts.user.key(), VaultError::Unauthorized);
+        require_keys_eq!(deposit.owner, self.user.key(), VaultError::Unauthorized);
         require_keys_eq!(deposit.vault, vault.key(), VaultError::VaultMismatch);
 
         let clock = Clock::get()?;
@@ -98,11 +239,11 @@ pub mod savings_vault {
         ];
 
         let transfer_principal = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
+            self.token_program.to_account_info(),
             Transfer {
-                from: ctx.accounts.vault_token_account.to_account_info(),
-                to: ctx.accounts.user_token_account.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
+                from: self.vault_token_account.to_account_info(),
+                to: self.user_token_account.to_account_info(),
+                authority: self.vault.to_account_info(),
             },
             signer_seeds,
         );
@@ -110,21 +251,31 @@ pub mod savings_vault {
 
         if reward > 0 {
             let transfer_reward = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
+                self.token_program.to_account_info(),
                 Transfer {
-                    from: ctx.accounts.reward_vault.to_account_info(),
-                    to: ctx.accounts.user_reward_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
+                    from: self.reward_vault.to_account_info(),
+                    to: self.user_reward_account.to_account_info(),
+                    authority: self.vault.to_account_info(),
                 },
                 signer_seeds,
             );
             token::transfer(transfer_reward, reward)?;
         }
 
-        deposit.claimed = true;
+        self.deposit.claimed = true;
 
-        Ok(())
+        Ok(reward)
+    }
+}
+
+/// Whether `project` is still an open funding_hub project before its deadline.
+fn accepts_pledges(project: &AccountInfo, now: i64) -> Result<bool> {
+    if project.owner != &funding_hub::ID || project.data_is_empty() {
+        return Ok(false);
     }
+    let data = project.try_borrow_data()?;
+    let project = Project::try_deserialize(&mut &data[..])?;
+    Ok(matches!(project.status, ProjectStatus::Active) && now < project.deadline_ts)
 }
 
 fn calculate_reward(amount: u64, apy_bps: u16, term_slots: u64, slots_per_year: u64) -> Result<u64> {
@@ -148,6 +299,55 @@ fn calculate_reward(amount: u64, apy_bps: u16, term_slots: u64, slots_per_year:
 
 pub const SLOTS_PER_YEAR_ESTIMATE: u64 = 63_072_000; // approx slots in a year at 0.5s per slot
 
+// Instruction families the guardian can pause independently via `Config.paused`
+pub const PAUSE_VAULTS: u16 = 1 << 0;
+pub const PAUSE_DEPOSITS: u16 = 1 << 1;
+pub const PAUSE_CLAIMS: u16 = 1 << 2;
+pub const PAUSE_ALL: u16 = PAUSE_VAULTS | PAUSE_DEPOSITS | PAUSE_CLAIMS;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = Config::LEN,
+        seeds = [Config::SEED_PREFIX],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, SavingsVault>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ VaultError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ VaultError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        has_one = guardian @ VaultError::Unauthorized,
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
 #[derive(Accounts)]
 #[instruction(vault_id: [u8; 8])]
 pub struct InitializeVault<'info> {
@@ -177,12 +377,28 @@ pub struct InitializeVault<'info> {
         associated_token::authority = vault
     )]
     pub reward_vault: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_VAULTS) @ VaultError::VaultsPaused
+    )]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Legacy layout; owner checked here, discriminator and size in the handler
+    #[account(mut, owner = crate::ID @ VaultError::Unauthorized)]
+    pub vault: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     #[account(mut)]
@@ -212,13 +428,46 @@ pub struct Deposit<'info> {
         constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::VaultMismatch
     )]
     pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_DEPOSITS) @ VaultError::DepositsPaused
+    )]
+    pub config: Account<'info, Config>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct SetYieldTarget<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner @ VaultError::Unauthorized,
+        seeds = [DepositReceipt::SEED_PREFIX, deposit.vault.as_ref(), owner.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, DepositReceipt>,
+    #[account(address = deposit.vault @ VaultError::VaultMismatch)]
+    pub vault: Account<'info, Vault>,
+    /// Yield target to set; left out to clear it
+    #[account(constraint = project.mint == vault.reward_mint @ VaultError::MintMismatch)]
+    pub project: Option<Account<'info, Project>>,
+    // Clearing the target stays open while deposits are paused, so a saver can
+    // always get back to a plain `claim`
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = project.is_none() || !config.is_paused(PAUSE_DEPOSITS) @ VaultError::DepositsPaused
+    )]
+    pub config: Account<'info, Config>,
+}
+
 #[derive(Accounts)]
 pub struct Claim<'info> {
+    // Writable so `claim_and_pledge` can pass the saver on as the paying donor
+    #[account(mut)]
     pub user: Signer<'info>,
     #[account(
         mut,
@@ -254,9 +503,56 @@ pub struct Claim<'info> {
         constraint = user_reward_account.mint == vault.reward_mint @ VaultError::MintMismatch
     )]
     pub user_reward_account: Account<'info, TokenAccount>,
+    #[account(
+        seeds = [Config::SEED_PREFIX],
+        bump = config.bump,
+        constraint = !config.is_paused(PAUSE_CLAIMS) @ VaultError::ClaimsPaused
+    )]
+    pub config: Account<'info, Config>,
     pub token_program: Program<'info, Token>,
 }
 
+/// `Claim` plus the funding_hub `pledge` accounts for the receipt's yield target.
+/// Project, donation and badge accounts are validated by funding_hub itself.
+#[derive(Accounts)]
+pub struct ClaimAndPledge<'info> {
+    pub claim: Claim<'info>,
+    /// CHECK: Must equal `deposit.yield_target`; may have closed since it was set,
+    /// otherwise funding_hub validates it
+    #[account(mut)]
+    pub project: UncheckedAccount<'info>,
+    /// CHECK: Validated by funding_hub
+    #[account(mut)]
+    pub project_vault: UncheckedAccount<'info>,
+    /// CHECK: Validated by funding_hub
+    #[account(mut)]
+    pub badge_mint: UncheckedAccount<'info>,
+    /// CHECK: Created or validated by funding_hub
+    #[account(mut)]
+    pub donor_badge_account: UncheckedAccount<'info>,
+    /// CHECK: Created or validated by funding_hub
+    #[account(mut)]
+    pub donation: UncheckedAccount<'info>,
+    /// CHECK: funding_hub's pause config, validated by funding_hub
+    pub funding_config: UncheckedAccount<'info>,
+    /// CHECK: Donor badge accounts, needed once the saver holds a donor badge;
+    /// validated by funding_hub
+    pub badge_config: Option<UncheckedAccount<'info>>,
+    /// CHECK: Validated by funding_hub
+    pub donor_badge_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: Validated by funding_hub
+    #[account(mut)]
+    pub donor_badge_metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: Validated by funding_hub
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Validated by funding_hub
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    pub funding_hub_program: Program<'info, FundingHub>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
 #[account]
 pub struct Vault {
     pub authority: Pubkey,
@@ -270,6 +566,7 @@ pub struct Vault {
     pub total_deposited: u64,
     pub bump: u8,
     pub reserved: [u8; 5],
+    pub version: u8,
 }
 
 impl Vault {
@@ -284,8 +581,57 @@ impl Vault {
         + 2
         + 8
         + 1
-        + 5;
+        + 5
+        + 1; // version
     pub const SEED_PREFIX: &'static [u8] = b"vault";
+    /// Layout written by `initialize_vault`; accounts without a version byte are v1.
+    pub const VERSION: u8 = 2;
+}
+
+/// `Vault` as laid out before accounts carried a version byte.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VaultV1 {
+    pub authority: Pubkey,
+    pub vault_id: [u8; 8],
+    pub deposit_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub reward_vault: Pubkey,
+    pub term_slots: u64,
+    pub apy_bps: u16,
+    pub total_deposited: u64,
+    pub bump: u8,
+    pub reserved: [u8; 5],
+}
+
+impl VaultV1 {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 32 + 32 + 32 + 8 + 2 + 8 + 1 + 5;
+
+    /// Parses raw v1 account data, discriminator included.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= 8 && data[..8] == Vault::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    pub fn migrate(self) -> Vault {
+        Vault {
+            authority: self.authority,
+            vault_id: self.vault_id,
+            deposit_mint: self.deposit_mint,
+            reward_mint: self.reward_mint,
+            vault_token_account: self.vault_token_account,
+            reward_vault: self.reward_vault,
+            term_slots: self.term_slots,
+            apy_bps: self.apy_bps,
+            total_deposited: self.total_deposited,
+            bump: self.bump,
+            reserved: self.reserved,
+            version: Vault::VERSION,
+        }
+    }
 }
 
 #[account]
@@ -298,11 +644,41 @@ pub struct DepositReceipt {
     pub claimed: bool,
     pub bump: u8,
     pub reserved: [u8; 7],
+    pub version: u8,
+    /// funding_hub project that receives the reward on claim; default means none.
+    pub yield_target: Pubkey,
 }
 
 impl DepositReceipt {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 7;
+    // The pre-versioning LEN counted `claimed` and `bump` as one byte, so
+    // `deposit` could never serialize a receipt and there are no v1 accounts.
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 7 + 1 + 32;
     pub const SEED_PREFIX: &'static [u8] = b"deposit";
+    pub const VERSION: u8 = 2;
+}
+
+/// Program-wide emergency controls; the guardian flips `paused` bits per instruction family.
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    pub paused: u16,
+    pub bump: u8,
+    pub reserved: [u8; 16],
+}
+
+impl Config {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // admin
+        + 32 // guardian
+        + 2 // paused
+        + 1 // bump
+        + 16; // reserved
+    pub const SEED_PREFIX: &'static [u8] = b"config";
+
+    pub fn is_paused(&self, family: u16) -> bool {
+        self.paused & family != 0
+    }
 }
 
 #[error_code]
@@ -327,4 +703,85 @@ pub enum VaultError {
     VaultMismatch,
     #[msg("Bump missing")]
     BumpNotFound,
+    #[msg("Account is already on the current layout")]
+    AlreadyMigrated,
+    #[msg("Deposit yield is assigned to a project; use claim_and_pledge")]
+    YieldTargetAssigned,
+    #[msg("Deposit has no yield target")]
+    NoYieldTarget,
+    #[msg("Project does not match the deposit's yield target")]
+    YieldTargetMismatch,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("Vault creation is paused")]
+    VaultsPaused,
+    #[msg("Deposits are paused")]
+    DepositsPaused,
+    #[msg("Claims are paused")]
+    ClaimsPaused,
+    #[msg("Account layout version is not supported")]
+    UnsupportedVersion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raw `Vault` bytes exactly as the pre-versioning program wrote them.
+    fn vault_v1_fixture() -> Vec<u8> {
+        let mut data = Vec::with_capacity(VaultV1::LEN);
+        data.extend_from_slice(&Vault::DISCRIMINATOR);
+        data.extend_from_slice(&[1u8; 32]); // authority
+        data.extend_from_slice(b"savings1"); // vault_id
+        data.extend_from_slice(&[2u8; 32]); // deposit_mint
+        data.extend_from_slice(&[3u8; 32]); // reward_mint
+        data.extend_from_slice(&[4u8; 32]); // vault_token_account
+        data.extend_from_slice(&[5u8; 32]); // reward_vault
+        data.extend_from_slice(&1_000_000u64.to_le_bytes()); // term_slots
+        data.extend_from_slice(&750u16.to_le_bytes()); // apy_bps
+        data.extend_from_slice(&42_000u64.to_le_bytes()); // total_deposited
+        data.push(252); // bump
+        data.extend_from_slice(&[0u8; 5]); // reserved
+        data
+    }
+
+    #[test]
+    fn migrates_v1_vault_fixture() {
+        let data = vault_v1_fixture();
+        assert_eq!(data.len(), VaultV1::LEN);
+
+        let vault = VaultV1::try_from_bytes(&data).unwrap().migrate();
+        let mut migrated = vec![0u8; Vault::LEN];
+        let mut writer: &mut [u8] = &mut migrated;
+        vault.try_serialize(&mut writer).unwrap();
+        let vault = Vault::try_deserialize(&mut &migrated[..]).unwrap();
+
+        assert_eq!(vault.version, Vault::VERSION);
+        assert_eq!(&vault.vault_id, b"savings1");
+        assert_eq!(vault.reward_vault, Pubkey::new_from_array([5u8; 32]));
+        assert_eq!(vault.term_slots, 1_000_000);
+        assert_eq!(vault.apy_bps, 750);
+        assert_eq!(vault.total_deposited, 42_000);
+        assert_eq!(vault.bump, 252);
+    }
+
+    #[test]
+    fn deposit_receipt_len_fits_serialized_receipt() {
+        let receipt = DepositReceipt {
+            vault: Pubkey::default(),
+            owner: Pubkey::default(),
+            amount: 0,
+            start_slot: 0,
+            unlock_slot: 0,
+            claimed: false,
+            bump: 0,
+            reserved: [0; 7],
+            version: DepositReceipt::VERSION,
+            yield_target: Pubkey::default(),
+        };
+        let mut data = Vec::new();
+        receipt.try_serialize(&mut data).unwrap();
+
+        assert_eq!(data.len(), DepositReceipt::LEN);
+    }
 }
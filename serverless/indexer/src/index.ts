@@ -151,33 +151,33 @@ async function refreshSnapshot(env: Env): Promise<Snapshot> {
 }
 
 async function fetchProjects(env: Env): Promise<ProjectSummary[]> {
-  const accounts = await getProgramAccounts(env, env.FUNDING_HUB_PROGRAM_ID, 184);
+  const accounts = await getProgramAccounts(env, env.FUNDING_HUB_PROGRAM_ID, "Project");
   return accounts.map((entry) => parseProject(entry));
 }
 
 async function fetchDaos(env: Env): Promise<DaoSummary[]> {
   if (!env.DAO_PASS_PROGRAM_ID) return [];
-  const accounts = await getProgramAccounts(env, env.DAO_PASS_PROGRAM_ID, 168);
+  const accounts = await getProgramAccounts(env, env.DAO_PASS_PROGRAM_ID, "Dao");
   return accounts.map((entry) => parseDao(entry));
 }
 
 async function fetchProposals(env: Env): Promise<ProposalSummary[]> {
   if (!env.GOVERNANCE_PROGRAM_ID) return [];
-  // Proposals are larger (discriminator + struct = 200 bytes)
-  const accounts = await getProgramAccounts(env, env.GOVERNANCE_PROGRAM_ID);
-  return accounts
-    .filter((account) => account.account.data[0].length >= 8 + 200)
-    .map((entry) => parseProposal(entry));
+  const accounts = await getProgramAccounts(env, env.GOVERNANCE_PROGRAM_ID, "Proposal");
+  return accounts.map((entry) => parseProposal(entry));
 }
 
 async function fetchVaults(env: Env): Promise<VaultSummary[]> {
   if (!env.SAVINGS_VAULT_PROGRAM_ID) return [];
-  const accounts = await getProgramAccounts(env, env.SAVINGS_VAULT_PROGRAM_ID, 197);
+  const accounts = await getProgramAccounts(env, env.SAVINGS_VAULT_PROGRAM_ID, "Vault");
   return accounts.map((entry) => parseVault(entry));
 }
 
-async function getProgramAccounts(env: Env, programId: string, dataSize?: number): Promise<RpcAccount[]> {
-  const filters = dataSize ? [{ dataSize }] : [];
+// Accounts are matched on their Anchor discriminator rather than their size, so
+// every layout version of an account is picked up. The parsers only read fields
+// shared by all versions.
+async function getProgramAccounts(env: Env, programId: string, accountName: string): Promise<RpcAccount[]> {
+  const filters = [{ memcmp: { offset: 0, bytes: await accountDiscriminator(accountName) } }];
   const body = {
     jsonrpc: "2.0",
     id: "getProgramAccounts",
@@ -382,6 +382,11 @@ function proposalStatusToString(status: number): "Pending" | "Succeeded" | "Defe
   }
 }
 
+async function accountDiscriminator(accountName: string): Promise<string> {
+  const hash = await crypto.subtle.digest("SHA-256", new TextEncoder().encode(`account:${accountName}`));
+  return base58Encode(new Uint8Array(hash).slice(0, 8));
+}
+
 const BASE58_ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
 
 function base58Encode(buffer: Uint8Array): string {
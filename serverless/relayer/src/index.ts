@@ -3,6 +3,7 @@ import bs58 from "bs58";
 
 type RelayPayload = {
   dao: string;
+  member?: string; // wallet whose DAO membership pays for the relay
   serializedTransaction: string; // base64 or base58
   encoding?: "base64" | "base58";
   maxComputeUnitPrice?: number;
@@ -21,6 +22,11 @@ interface Env {
   DAO_INDEX_URL?: string;
 }
 
+// `Member` layout in dao_pass: discriminator, dao, owner, membership_mint,
+// member_pass_account, joined_ts, mint_count, expires_ts, roles, reserved, version
+const MEMBER_EXPIRES_TS_OFFSET = 152;
+const MEMBER_REVOKED_OFFSET = 169;
+
 const DEFAULT_ALLOWED = [
   "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", // SPL Token
   "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", // Jupiter V6
@@ -69,6 +75,15 @@ export default {
         await validateDaoBudget(env.DAO_INDEX_URL, payload.dao);
       }
 
+      // A revoked pass stays in the wallet (frozen), so check the Member record itself
+      const membershipProgram = env.DAO_PASS_PROGRAM_ID ? new PublicKey(env.DAO_PASS_PROGRAM_ID) : null;
+      if (membershipProgram) {
+        if (!payload.member) {
+          return Response.json({ error: "Missing member" }, { status: 400 });
+        }
+        await validateMembership(connection, membershipProgram, payload.dao, payload.member);
+      }
+
       let rawTx: Uint8Array;
       if (payload.encoding === "base58") {
         rawTx = bs58.decode(payload.serializedTransaction);
@@ -93,6 +108,10 @@ export default {
           }
         }
 
+        if (membershipProgram) {
+          enforceMemberSigner(accountKeys.slice(0, message.header.numRequiredSignatures), payload.member!);
+        }
+
         // Sign and send versioned transaction
         versionedTx.sign([feePayer]);
         signature = await connection.sendTransaction(versionedTx, {
@@ -105,6 +124,9 @@ export default {
         transaction.feePayer = feePayer.publicKey;
 
         enforceAllowedPrograms(transaction, allowedPrograms);
+        if (membershipProgram) {
+          enforceMemberSigner(transaction.signatures.map((entry) => entry.publicKey), payload.member!);
+        }
 
         // Optionally adjust compute price for priority fees
         if (payload.maxComputeUnitPrice) {
@@ -149,6 +171,39 @@ async function validateDaoBudget(indexUrl: string, dao: string): Promise<void> {
   }
 }
 
+async function validateMembership(
+  connection: Connection,
+  programId: PublicKey,
+  dao: string,
+  member: string
+): Promise<void> {
+  const [memberPda] = PublicKey.findProgramAddressSync(
+    [new TextEncoder().encode("member"), new PublicKey(dao).toBytes(), new PublicKey(member).toBytes()],
+    programId
+  );
+  const info = await connection.getAccountInfo(memberPda);
+  if (!info || !info.owner.equals(programId)) {
+    throw new Error(`Wallet ${member} is not a member of DAO ${dao}`);
+  }
+  // Records not yet migrated predate revocation and expiry and read as active
+  if (info.data.length <= MEMBER_REVOKED_OFFSET) return;
+  if (info.data[MEMBER_REVOKED_OFFSET] === 1) {
+    throw new Error(`Membership of ${member} in DAO ${dao} was revoked`);
+  }
+  // Zero means a lifetime membership
+  const view = new DataView(info.data.buffer, info.data.byteOffset, info.data.byteLength);
+  const expiresTs = view.getBigInt64(MEMBER_EXPIRES_TS_OFFSET, true);
+  if (expiresTs !== 0n && expiresTs <= BigInt(Math.floor(Date.now() / 1000))) {
+    throw new Error(`Membership of ${member} in DAO ${dao} has expired`);
+  }
+}
+
+function enforceMemberSigner(signers: PublicKey[], member: string) {
+  if (!signers.some((signer) => signer.toBase58() === member)) {
+    throw new Error(`Transaction is not signed by member ${member}`);
+  }
+}
+
 function enforceAllowedPrograms(transaction: Transaction, allowlist: Set<string>) {
   for (const ix of transaction.instructions) {
     const programId = ix.programId.toBase58();
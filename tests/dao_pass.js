@@ -0,0 +1,590 @@
+const anchor = require("@coral-xyz/anchor");
+const assert = require("assert");
+const {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  getAccount,
+  getAssociatedTokenAddressSync
+} = require("@solana/spl-token");
+
+const { SystemProgram, SYSVAR_RENT_PUBKEY, PublicKey, Keypair, LAMPORTS_PER_SOL } = anchor.web3;
+
+const BPF_UPGRADEABLE_LOADER_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+describe("dao_pass", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.DaoPass;
+  const authority = provider.wallet;
+  const connection = provider.connection;
+
+  let sponsorMint;
+  let authoritySponsorAta;
+  let configPda;
+
+  const pda = (seeds) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const sleep = (ms) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  const expectError = (attempt, errorCode) =>
+    assert.rejects(attempt, (err) => {
+      assert.strictEqual(err.error.errorCode.code, errorCode);
+      return true;
+    });
+
+  const fundedWallet = async (sponsorAmount = 0) => {
+    const keypair = Keypair.generate();
+    await connection.confirmTransaction(await connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL));
+    const tokenAccount = (
+      await getOrCreateAssociatedTokenAccount(connection, authority.payer, sponsorMint, keypair.publicKey)
+    ).address;
+    if (sponsorAmount > 0) {
+      await mintTo(connection, authority.payer, sponsorMint, tokenAccount, authority.publicKey, sponsorAmount);
+    }
+    return { keypair, tokenAccount };
+  };
+
+  const createDao = async (label, { maxRelaySpend = 1_000_000, windowSecs = 3_600, window = { fixed: {} } } = {}) => {
+    const nameSeed = Buffer.from(label);
+    const dao = pda([Buffer.from("dao"), authority.publicKey.toBuffer(), nameSeed]);
+    const passMint = Keypair.generate();
+    const sponsorVault = pda([Buffer.from("sponsor_vault"), dao.toBuffer()]);
+    await program.methods
+      .initializeDao(Array.from(nameSeed), sponsorMint, new anchor.BN(maxRelaySpend), new anchor.BN(windowSecs), window)
+      .accounts({
+        authority: authority.publicKey,
+        dao,
+        passMint: passMint.publicKey,
+        sponsorMintAccount: sponsorMint,
+        sponsorVault,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .signers([passMint])
+      .rpc();
+    return { dao, passMint: passMint.publicKey, sponsorVault };
+  };
+
+  const memberPdaFor = ({ dao }, owner) => pda([Buffer.from("member"), dao.toBuffer(), owner.toBuffer()]);
+
+  // Issues a pass to `wallet`, or to the authority itself when no wallet is given
+  const issuePass = async (daoAccounts, wallet = null) => {
+    const owner = wallet ? wallet.keypair.publicKey : authority.publicKey;
+    await program.methods
+      .issuePass()
+      .accounts({
+        authority: authority.publicKey,
+        memberWallet: owner,
+        dao: daoAccounts.dao,
+        passMint: daoAccounts.passMint,
+        member: memberPdaFor(daoAccounts, owner),
+        memberPassAccount: getAssociatedTokenAddressSync(daoAccounts.passMint, owner),
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .signers(wallet ? [wallet.keypair] : [])
+      .rpc();
+    return memberPdaFor(daoAccounts, owner);
+  };
+
+  const receiptPdaFor = ({ dao }, messageHash) =>
+    pda([Buffer.from("relay_receipt"), dao.toBuffer(), Buffer.from(messageHash)]);
+
+  // Records relay spend for `member`, signed by the authority unless a registered relayer is given
+  const recordRelaySpend = async (daoAccounts, member, spend, { relayer = null, messageHash = null } = {}) => {
+    const hash = messageHash || Array.from(Keypair.generate().publicKey.toBytes());
+    await program.methods
+      .recordRelaySpend(new anchor.BN(spend), hash)
+      .accounts({
+        dao: daoAccounts.dao,
+        signer: relayer ? relayer.keypair.publicKey : authority.publicKey,
+        relayer: relayer ? relayer.registry : null,
+        member,
+        roleBudget: null,
+        receipt: receiptPdaFor(daoAccounts, hash),
+        config: configPda,
+        systemProgram: SystemProgram.programId
+      })
+      .signers(relayer ? [relayer.keypair] : [])
+      .rpc();
+    return hash;
+  };
+
+  const setMembershipTerms = ({ dao }, durationSecs, duesAmount) =>
+    program.methods
+      .setMembershipTerms(new anchor.BN(durationSecs), new anchor.BN(duesAmount))
+      .accounts({ authority: authority.publicKey, dao, config: configPda })
+      .rpc();
+
+  const fundSponsorVault = (daoAccounts, funder, amount) =>
+    program.methods
+      .fundSponsorVault(new anchor.BN(amount))
+      .accounts({
+        funder: funder.keypair.publicKey,
+        dao: daoAccounts.dao,
+        funderTokenAccount: funder.tokenAccount,
+        sponsorVault: daoAccounts.sponsorVault,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .signers([funder.keypair])
+      .rpc();
+
+  const relayerPdaFor = ({ dao }, relayerKey) => pda([Buffer.from("relayer"), dao.toBuffer(), relayerKey.toBuffer()]);
+
+  // Registers a funded relayer wallet; the result can sign `recordRelaySpend`
+  const addRelayer = async (daoAccounts, maxRelaySpend, expiresTs = 0, wallet = null) => {
+    const relayer = wallet || (await fundedWallet());
+    const registry = relayerPdaFor(daoAccounts, relayer.keypair.publicKey);
+    await program.methods
+      .addRelayer(relayer.keypair.publicKey, new anchor.BN(maxRelaySpend), new anchor.BN(expiresTs))
+      .accounts({
+        authority: authority.publicKey,
+        dao: daoAccounts.dao,
+        relayer: registry,
+        config: configPda,
+        systemProgram: SystemProgram.programId
+      })
+      .rpc();
+    return { ...relayer, registry };
+  };
+
+  before(async () => {
+    configPda = pda([Buffer.from("config")]);
+    if (!(await connection.getAccountInfo(configPda))) {
+      await program.methods
+        .initializeConfig(authority.publicKey)
+        .accounts({
+          admin: authority.publicKey,
+          config: configPda,
+          program: program.programId,
+          programData: PublicKey.findProgramAddressSync([program.programId.toBuffer()], BPF_UPGRADEABLE_LOADER_ID)[0],
+          systemProgram: SystemProgram.programId
+        })
+        .rpc();
+    }
+
+    sponsorMint = await createMint(connection, authority.payer, authority.publicKey, null, 0);
+    authoritySponsorAta = (
+      await getOrCreateAssociatedTokenAccount(connection, authority.payer, sponsorMint, authority.publicKey)
+    ).address;
+    await mintTo(connection, authority.payer, sponsorMint, authoritySponsorAta, authority.publicKey, 1_000_000);
+  });
+
+  describe("membership renewals", () => {
+    const renew = (daoAccounts, member, payer) =>
+      program.methods
+        .renewMembership()
+        .accounts({
+          payer: payer.keypair.publicKey,
+          dao: daoAccounts.dao,
+          member,
+          sponsorMint,
+          payerTokenAccount: payer.tokenAccount,
+          duesTreasury: getAssociatedTokenAddressSync(sponsorMint, daoAccounts.dao, true),
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([payer.keypair])
+        .rpc();
+
+    it("stops relaying for an expired member until the membership is renewed", async () => {
+      const daoAccounts = await createDao("renew001");
+      const lifetimeMember = await issuePass(daoAccounts);
+      const holder = await fundedWallet(1_000);
+
+      await expectError(renew(daoAccounts, lifetimeMember, holder), "MembershipNotExpiring");
+
+      await setMembershipTerms(daoAccounts, 2, 100);
+      const member = await issuePass(daoAccounts, holder);
+      await expectError(renew(daoAccounts, lifetimeMember, holder), "LifetimeMembership");
+
+      await sleep(3_000);
+      await expectError(recordRelaySpend(daoAccounts, member, 10), "MemberInactive");
+
+      // A lapsed term restarts from now rather than from the old expiry
+      await setMembershipTerms(daoAccounts, 3_600, 100);
+      await renew(daoAccounts, member, holder);
+      const renewed = await program.account.member.fetch(member);
+      assert.ok(renewed.expiresTs.toNumber() > Math.floor(Date.now() / 1000) + 3_000);
+      await recordRelaySpend(daoAccounts, member, 10);
+
+      const treasury = await getAccount(connection, getAssociatedTokenAddressSync(sponsorMint, daoAccounts.dao, true));
+      assert.strictEqual(Number(treasury.amount), 100);
+      const holderAccount = await getAccount(connection, holder.tokenAccount);
+      assert.strictEqual(Number(holderAccount.amount), 900);
+    });
+
+    it("extends an unexpired membership from its current expiry", async () => {
+      const daoAccounts = await createDao("renew002");
+      await setMembershipTerms(daoAccounts, 3_600, 0);
+      const holder = await fundedWallet();
+      const member = await issuePass(daoAccounts, holder);
+      const before = (await program.account.member.fetch(member)).expiresTs.toNumber();
+
+      await renew(daoAccounts, member, holder);
+      const after = (await program.account.member.fetch(member)).expiresTs.toNumber();
+      assert.strictEqual(after, before + 3_600);
+    });
+  });
+
+  describe("self-serve joins", () => {
+    const joinRequestPdaFor = ({ dao }, applicant) =>
+      pda([Buffer.from("join_request"), dao.toBuffer(), applicant.keypair.publicKey.toBuffer()]);
+
+    const setJoinPolicy = ({ dao }, joinPolicy) =>
+      program.methods
+        .setJoinPolicy(joinPolicy, Array(32).fill(0))
+        .accounts({ authority: authority.publicKey, dao, config: configPda })
+        .rpc();
+
+    const requestJoin = (daoAccounts, applicant) =>
+      program.methods
+        .requestJoin()
+        .accounts({
+          applicant: applicant.keypair.publicKey,
+          dao: daoAccounts.dao,
+          joinRequest: joinRequestPdaFor(daoAccounts, applicant),
+          config: configPda,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([applicant.keypair])
+        .rpc();
+
+    const reviewJoinRequests = (daoAccounts, decisions) =>
+      program.methods
+        .reviewJoinRequests(decisions.map(([, approve]) => approve))
+        .accounts({ authority: authority.publicKey, dao: daoAccounts.dao, config: configPda })
+        .remainingAccounts(
+          decisions.flatMap(([applicant]) => [
+            { pubkey: joinRequestPdaFor(daoAccounts, applicant), isSigner: false, isWritable: true },
+            { pubkey: applicant.keypair.publicKey, isSigner: false, isWritable: true }
+          ])
+        )
+        .rpc();
+
+    const joinDao = (daoAccounts, applicant, { withRequest = false, paysDues = false } = {}) =>
+      program.methods
+        .joinDao([])
+        .accounts({
+          applicant: applicant.keypair.publicKey,
+          dao: daoAccounts.dao,
+          passMint: daoAccounts.passMint,
+          member: memberPdaFor(daoAccounts, applicant.keypair.publicKey),
+          memberPassAccount: getAssociatedTokenAddressSync(daoAccounts.passMint, applicant.keypair.publicKey),
+          joinRequest: withRequest ? joinRequestPdaFor(daoAccounts, applicant) : null,
+          payerTokenAccount: paysDues ? applicant.tokenAccount : null,
+          sponsorMint: paysDues ? sponsorMint : null,
+          duesTreasury: paysDues ? getAssociatedTokenAddressSync(sponsorMint, daoAccounts.dao, true) : null,
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY
+        })
+        .signers([applicant.keypair])
+        .rpc();
+
+    it("admits approved applicants and refunds rejected ones", async () => {
+      const daoAccounts = await createDao("join0001");
+      const accepted = await fundedWallet(500);
+      const rejected = await fundedWallet(500);
+      const pending = await fundedWallet(500);
+
+      await expectError(requestJoin(daoAccounts, accepted), "JoinPolicyMismatch");
+      await expectError(joinDao(daoAccounts, accepted), "JoinPolicyMismatch");
+
+      await setJoinPolicy(daoAccounts, { application: {} });
+      await setMembershipTerms(daoAccounts, 0, 50);
+      await requestJoin(daoAccounts, accepted);
+      await requestJoin(daoAccounts, rejected);
+      await requestJoin(daoAccounts, pending);
+
+      const rejectedLamports = await connection.getBalance(rejected.keypair.publicKey);
+      await reviewJoinRequests(daoAccounts, [
+        [accepted, true],
+        [rejected, false]
+      ]);
+      assert.strictEqual(await connection.getAccountInfo(joinRequestPdaFor(daoAccounts, rejected)), null);
+      assert.ok((await connection.getBalance(rejected.keypair.publicKey)) > rejectedLamports);
+      assert.strictEqual((await program.account.joinRequest.fetch(joinRequestPdaFor(daoAccounts, accepted))).approved, true);
+      await expectError(reviewJoinRequests(daoAccounts, [[accepted, true]]), "JoinRequestAlreadyReviewed");
+
+      await expectError(joinDao(daoAccounts, pending, { withRequest: true, paysDues: true }), "JoinRequestNotApproved");
+      await expectError(joinDao(daoAccounts, accepted, { paysDues: true }), "JoinRequestNotApproved");
+      await expectError(joinDao(daoAccounts, accepted, { withRequest: true }), "DuesAccountsMissing");
+
+      // The first paid join creates the dues treasury
+      await joinDao(daoAccounts, accepted, { withRequest: true, paysDues: true });
+      assert.strictEqual(await connection.getAccountInfo(joinRequestPdaFor(daoAccounts, accepted)), null);
+      const member = await program.account.member.fetch(memberPdaFor(daoAccounts, accepted.keypair.publicKey));
+      assert.ok(member.owner.equals(accepted.keypair.publicKey));
+      const pass = await getAccount(
+        connection,
+        getAssociatedTokenAddressSync(daoAccounts.passMint, accepted.keypair.publicKey)
+      );
+      assert.strictEqual(Number(pass.amount), 1);
+      assert.strictEqual(pass.isFrozen, true);
+      const treasury = await getAccount(connection, getAssociatedTokenAddressSync(sponsorMint, daoAccounts.dao, true));
+      assert.strictEqual(Number(treasury.amount), 50);
+      assert.strictEqual((await program.account.dao.fetch(daoAccounts.dao)).totalMembers, 1);
+    });
+
+    it("lets anyone join an open DAO without dues", async () => {
+      const daoAccounts = await createDao("join0002");
+      await setJoinPolicy(daoAccounts, { open: {} });
+      const applicant = await fundedWallet();
+
+      await joinDao(daoAccounts, applicant);
+      const member = await program.account.member.fetch(memberPdaFor(daoAccounts, applicant.keypair.publicKey));
+      assert.strictEqual(member.expiresTs.toNumber(), 0);
+      await assert.rejects(joinDao(daoAccounts, applicant));
+    });
+  });
+
+  describe("sponsor vault", () => {
+    const withdrawSponsorFunds = (daoAccounts, amount, signer = null) =>
+      program.methods
+        .withdrawSponsorFunds(new anchor.BN(amount))
+        .accounts({
+          authority: signer ? signer.keypair.publicKey : authority.publicKey,
+          dao: daoAccounts.dao,
+          sponsorVault: daoAccounts.sponsorVault,
+          destination: signer ? signer.tokenAccount : authoritySponsorAta,
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers(signer ? [signer.keypair] : [])
+        .rpc();
+
+    it("takes deposits from anyone and pays out only to the authority", async () => {
+      const daoAccounts = await createDao("sponsor1");
+      const funder = await fundedWallet(1_000);
+
+      await expectError(fundSponsorVault(daoAccounts, funder, 0), "InvalidAmount");
+      await fundSponsorVault(daoAccounts, funder, 1_000);
+      let vault = await getAccount(connection, daoAccounts.sponsorVault);
+      assert.strictEqual(Number(vault.amount), 1_000);
+      assert.ok(vault.owner.equals(daoAccounts.dao), "the DAO PDA holds the sponsor funds");
+
+      await expectError(withdrawSponsorFunds(daoAccounts, 100, funder), "Unauthorized");
+      await expectError(withdrawSponsorFunds(daoAccounts, 0), "InvalidAmount");
+      await assert.rejects(withdrawSponsorFunds(daoAccounts, 1_001));
+
+      const before = await getAccount(connection, authoritySponsorAta);
+      await withdrawSponsorFunds(daoAccounts, 400);
+      const after = await getAccount(connection, authoritySponsorAta);
+      assert.strictEqual(Number(after.amount - before.amount), 400);
+      vault = await getAccount(connection, daoAccounts.sponsorVault);
+      assert.strictEqual(Number(vault.amount), 600);
+    });
+  });
+
+  describe("member relay quotas", () => {
+    const setMemberRelayCap = ({ dao }, cap) =>
+      program.methods
+        .setMemberRelayCap(new anchor.BN(cap))
+        .accounts({ authority: authority.publicKey, dao, config: configPda })
+        .rpc();
+
+    it("caps each member separately under the DAO-wide cap", async () => {
+      const daoAccounts = await createDao("quota001", { maxRelaySpend: 150 });
+      const first = await issuePass(daoAccounts, await fundedWallet());
+      const second = await issuePass(daoAccounts, await fundedWallet());
+
+      // Spend is tracked before any cap exists
+      await recordRelaySpend(daoAccounts, first, 40);
+      await setMemberRelayCap(daoAccounts, 100);
+      await recordRelaySpend(daoAccounts, first, 60);
+      await expectError(recordRelaySpend(daoAccounts, first, 1), "MemberRelayQuotaExceeded");
+
+      await recordRelaySpend(daoAccounts, second, 50);
+      await expectError(recordRelaySpend(daoAccounts, second, 1), "RelayBudgetExceeded");
+
+      const member = await program.account.member.fetch(first);
+      assert.strictEqual(member.relaySpent.toNumber(), 100);
+      const dao = await program.account.dao.fetch(daoAccounts.dao);
+      assert.strictEqual(dao.relaySpent.toNumber(), 150);
+
+      // Lifting the cap leaves only the DAO-wide cap
+      await setMemberRelayCap(daoAccounts, 0);
+      await expectError(recordRelaySpend(daoAccounts, first, 1), "RelayBudgetExceeded");
+    });
+  });
+
+  describe("delegated relayers", () => {
+    const chainTime = async () => connection.getBlockTime(await connection.getSlot());
+
+    it("lets a registered relayer record spend up to its own limit", async () => {
+      const daoAccounts = await createDao("relayer1");
+      const member = await issuePass(daoAccounts);
+      const wallet = await fundedWallet();
+
+      await expectError(addRelayer(daoAccounts, 0, 0, wallet), "InvalidLimit");
+      await expectError(addRelayer(daoAccounts, 100, (await chainTime()) - 10, wallet), "InvalidDuration");
+      const relayer = await addRelayer(daoAccounts, 100, 0, wallet);
+
+      const hash = await recordRelaySpend(daoAccounts, member, 70, { relayer });
+      const receipt = await program.account.relayReceipt.fetch(receiptPdaFor(daoAccounts, hash));
+      assert.ok(receipt.relayer.equals(relayer.keypair.publicKey));
+      await expectError(recordRelaySpend(daoAccounts, member, 40, { relayer }), "RelayerLimitExceeded");
+      const registry = await program.account.relayer.fetch(relayer.registry);
+      assert.strictEqual(registry.relaySpent.toNumber(), 70);
+
+      const stranger = await fundedWallet();
+      await expectError(
+        recordRelaySpend(daoAccounts, member, 10, { relayer: { ...stranger, registry: null } }),
+        "Unauthorized"
+      );
+
+      await program.methods
+        .removeRelayer()
+        .accounts({ authority: authority.publicKey, dao: daoAccounts.dao, relayer: relayer.registry, config: configPda })
+        .rpc();
+      assert.strictEqual(await connection.getAccountInfo(relayer.registry), null);
+      await expectError(
+        recordRelaySpend(daoAccounts, member, 10, { relayer: { ...relayer, registry: null } }),
+        "Unauthorized"
+      );
+    });
+
+    it("rejects a relayer whose key has expired", async () => {
+      const daoAccounts = await createDao("relayer2");
+      const member = await issuePass(daoAccounts);
+      const relayer = await addRelayer(daoAccounts, 100, (await chainTime()) + 2);
+
+      await sleep(4_000);
+      await expectError(recordRelaySpend(daoAccounts, member, 10, { relayer }), "RelayerExpired");
+    });
+  });
+
+  describe("relay receipts", () => {
+    const reimburse = (daoAccounts, messageHash, signer, relayerTokenAccount) =>
+      program.methods
+        .reimburseRelayer(messageHash)
+        .accounts({
+          signer: signer ? signer.keypair.publicKey : authority.publicKey,
+          dao: daoAccounts.dao,
+          receipt: receiptPdaFor(daoAccounts, messageHash),
+          sponsorVault: daoAccounts.sponsorVault,
+          relayerTokenAccount,
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers(signer ? [signer.keypair] : [])
+        .rpc();
+
+    const closeReceipt = (daoAccounts, messageHash, relayer) =>
+      program.methods
+        .closeRelayReceipt()
+        .accounts({
+          relayer: relayer.keypair.publicKey,
+          receipt: receiptPdaFor(daoAccounts, messageHash),
+          config: configPda
+        })
+        .signers([relayer.keypair])
+        .rpc();
+
+    it("records each relay once and reimburses each receipt once", async () => {
+      const daoAccounts = await createDao("receipt1");
+      await fundSponsorVault(daoAccounts, await fundedWallet(1_000), 1_000);
+      const member = await issuePass(daoAccounts);
+      const relayer = await addRelayer(daoAccounts, 1_000);
+      const stranger = await fundedWallet();
+
+      const hash = await recordRelaySpend(daoAccounts, member, 250, { relayer });
+      const receipt = await program.account.relayReceipt.fetch(receiptPdaFor(daoAccounts, hash));
+      assert.ok(receipt.dao.equals(daoAccounts.dao));
+      assert.ok(receipt.member.equals(authority.publicKey));
+      assert.strictEqual(receipt.spend.toNumber(), 250);
+      assert.strictEqual(receipt.reimbursed, false);
+
+      // The receipt PDA is keyed by the message hash, so a replay can't be recorded
+      await assert.rejects(recordRelaySpend(daoAccounts, member, 250, { relayer, messageHash: hash }));
+      assert.strictEqual((await program.account.dao.fetch(daoAccounts.dao)).relaySpent.toNumber(), 250);
+
+      await expectError(reimburse(daoAccounts, hash, stranger, relayer.tokenAccount), "Unauthorized");
+      await expectError(reimburse(daoAccounts, hash, relayer, stranger.tokenAccount), "RelayerAccountMismatch");
+      await reimburse(daoAccounts, hash, relayer, relayer.tokenAccount);
+      assert.strictEqual(Number((await getAccount(connection, relayer.tokenAccount)).amount), 250);
+      assert.strictEqual((await program.account.relayReceipt.fetch(receiptPdaFor(daoAccounts, hash))).reimbursed, true);
+      await expectError(reimburse(daoAccounts, hash, null, relayer.tokenAccount), "AlreadyReimbursed");
+
+      // Spend the authority records itself is reimbursed to the authority
+      const ownHash = await recordRelaySpend(daoAccounts, member, 100);
+      const before = await getAccount(connection, authoritySponsorAta);
+      await reimburse(daoAccounts, ownHash, null, authoritySponsorAta);
+      const after = await getAccount(connection, authoritySponsorAta);
+      assert.strictEqual(Number(after.amount - before.amount), 100);
+      assert.strictEqual(Number((await getAccount(connection, daoAccounts.sponsorVault)).amount), 650);
+    });
+
+    it("keeps receipts until the retention period has passed", async () => {
+      const daoAccounts = await createDao("receipt2");
+      const member = await issuePass(daoAccounts);
+      const relayer = await addRelayer(daoAccounts, 1_000);
+      const stranger = await fundedWallet();
+      const hash = await recordRelaySpend(daoAccounts, member, 10, { relayer });
+
+      await expectError(closeReceipt(daoAccounts, hash, stranger), "Unauthorized");
+      await expectError(closeReceipt(daoAccounts, hash, relayer), "ReceiptStillNeeded");
+      assert.ok(await connection.getAccountInfo(receiptPdaFor(daoAccounts, hash)));
+    });
+  });
+
+  describe("relay windows", () => {
+    const configureSponsor = (daoAccounts, cap, windowSecs, window) =>
+      program.methods
+        .configureSponsor(new anchor.BN(cap), new anchor.BN(windowSecs), window)
+        .accounts({
+          authority: authority.publicKey,
+          dao: daoAccounts.dao,
+          sponsorMint,
+          sponsorVault: daoAccounts.sponsorVault,
+          config: configPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY
+        })
+        .rpc();
+
+    it("rejects spend over the cap within a rolling window and frees it once the window passes", async () => {
+      await expectError(createDao("window00", { windowSecs: 0 }), "InvalidDuration");
+      const daoAccounts = await createDao("window01", { maxRelaySpend: 100, windowSecs: 2, window: { rolling: {} } });
+      let dao = await program.account.dao.fetch(daoAccounts.dao);
+      assert.deepStrictEqual(dao.relayWindow, { rolling: {} });
+      assert.strictEqual(dao.relayWindowSecs.toNumber(), 2);
+      const member = await issuePass(daoAccounts);
+
+      await recordRelaySpend(daoAccounts, member, 60);
+      await expectError(recordRelaySpend(daoAccounts, member, 50), "RelayBudgetExceeded");
+
+      await sleep(5_000);
+      await recordRelaySpend(daoAccounts, member, 100);
+
+      await expectError(configureSponsor(daoAccounts, 200, 0, { fixed: {} }), "InvalidDuration");
+      await expectError(configureSponsor(daoAccounts, 0, 3_600, { fixed: {} }), "InvalidLimit");
+
+      // Spend already in the window carries over into the new settings
+      await configureSponsor(daoAccounts, 200, 3_600, { fixed: {} });
+      dao = await program.account.dao.fetch(daoAccounts.dao);
+      assert.deepStrictEqual(dao.relayWindow, { fixed: {} });
+      assert.strictEqual(dao.relayWindowSecs.toNumber(), 3_600);
+      assert.strictEqual(dao.maxRelaySpend.toNumber(), 200);
+      await expectError(recordRelaySpend(daoAccounts, member, 101), "RelayBudgetExceeded");
+      await recordRelaySpend(daoAccounts, member, 100);
+    });
+  });
+});
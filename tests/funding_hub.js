@@ -1,4 +1,4 @@
-const anchor = require("@project-serum/anchor");
+const anchor = require("@coral-xyz/anchor");
 const assert = require("assert");
 const {
   TOKEN_PROGRAM_ID,
@@ -6,15 +6,26 @@ const {
   createMint,
   getOrCreateAssociatedTokenAccount,
   mintTo,
+  approve,
   getAccount,
   getAssociatedTokenAddressSync
 } = require("@solana/spl-token");
 
-const { SystemProgram, SYSVAR_RENT_PUBKEY, PublicKey, LAMPORTS_PER_SOL } = anchor.web3;
+const {
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  PublicKey,
+  Ed25519Program,
+  LAMPORTS_PER_SOL
+} = anchor.web3;
 
 const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
   "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
 );
+const BPF_UPGRADEABLE_LOADER_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
 
 describe("funding_hub", () => {
   const provider = anchor.AnchorProvider.local();
@@ -36,6 +47,139 @@ describe("funding_hub", () => {
   let badgeMetadataPda;
   let projectPda;
   let projectVaultAta;
+  let configPda;
+
+  const metadataPdaFor = (mint) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+  const projectPdaFor = (id) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("project"), authority.publicKey.toBuffer(), Buffer.from(id.toArray("le", 8))],
+      program.programId
+    )[0];
+
+  const initializeProjectAccounts = (id, badgeMint, overrides = {}) => {
+    const project = projectPdaFor(id);
+    return {
+      authority: authority.publicKey,
+      project,
+      mint: depositMint,
+      projectVault: getAssociatedTokenAddressSync(depositMint, project, true),
+      badgeMint,
+      badgeMetadata: metadataPdaFor(badgeMint),
+      config: configPda,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      rent: SYSVAR_RENT_PUBKEY,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      ...overrides
+    };
+  };
+
+  const expectInitializeProjectError = async (id, overrides, errorCode) => {
+    const badgeMint = anchor.web3.Keypair.generate();
+    await assert.rejects(
+      program.methods
+        .initializeProject(id, targetAmount, deadlineTs, "Spoofed", "SPOOF", "https://example.com/spoof.json")
+        .accounts(initializeProjectAccounts(id, badgeMint.publicKey, overrides))
+        .signers([badgeMint])
+        .rpc(),
+      (err) => {
+        assert.strictEqual(err.error.errorCode.code, errorCode);
+        return true;
+      }
+    );
+  };
+
+  const expectError = (attempt, errorCode) =>
+    assert.rejects(attempt, (err) => {
+      assert.strictEqual(err.error.errorCode.code, errorCode);
+      return true;
+    });
+
+  const createProject = async (id, target) => {
+    const badgeMint = anchor.web3.Keypair.generate();
+    await program.methods
+      .initializeProject(id, new anchor.BN(target), deadlineTs, "Side", "SIDE", "https://example.com/side.json")
+      .accounts(initializeProjectAccounts(id, badgeMint.publicKey))
+      .signers([badgeMint])
+      .rpc();
+    const project = projectPdaFor(id);
+    return {
+      project,
+      vault: getAssociatedTokenAddressSync(depositMint, project, true),
+      badgeMint: badgeMint.publicKey
+    };
+  };
+
+  const fundedDonor = async (amount) => {
+    const keypair = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(keypair.publicKey, LAMPORTS_PER_SOL)
+    );
+    const tokenAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, authority.payer, depositMint, keypair.publicKey)
+    ).address;
+    await mintTo(provider.connection, authority.payer, depositMint, tokenAccount, authority.publicKey, amount);
+    return { keypair, tokenAccount };
+  };
+
+  const donationPdaFor = (project, donor) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("donation"), project.toBuffer(), donor.toBuffer()],
+      program.programId
+    )[0];
+
+  const pledgeTo = ({ project, vault, badgeMint }, donor, amount, rewardTier = null) =>
+    program.methods
+      .pledge(new anchor.BN(amount), null)
+      .accounts({
+        donor: donor.keypair.publicKey,
+        donorTokenAccount: donor.tokenAccount,
+        project,
+        projectVault: vault,
+        badgeMint,
+        donorBadgeAccount: getAssociatedTokenAddressSync(badgeMint, donor.keypair.publicKey),
+        donation: donationPdaFor(project, donor.keypair.publicKey),
+        referral: null,
+        rewardTier,
+        badgeConfig: null,
+        donorBadgeMint: null,
+        donorBadgeMetadata: null,
+        tokenMetadataProgram: null,
+        sysvarInstructions: null,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .signers([donor.keypair])
+      .rpc();
+
+  const finalize = (project) =>
+    program.methods
+      .finalizeProject()
+      .accounts({ authority: authority.publicKey, project, config: configPda })
+      .rpc();
+
+  const withdrawFrom = ({ project, vault }, amount) =>
+    program.methods
+      .withdraw(new anchor.BN(amount))
+      .accounts({
+        authority: authority.publicKey,
+        project,
+        projectVault: vault,
+        authorityTokenAccount: authorityDepositAta,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .rpc();
 
   before(async () => {
     const connection = provider.connection;
@@ -83,12 +227,28 @@ describe("funding_hub", () => {
       pledgeAmountNumber * 2
     );
 
-    const projectSeed = Buffer.from("project");
-    const projectIdBytes = projectId.toArray("le", 8);
-    [projectPda] = PublicKey.findProgramAddressSync(
-      [projectSeed, authority.publicKey.toBuffer(), Buffer.from(projectIdBytes)],
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
       program.programId
     );
+    const [programDataPda] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_UPGRADEABLE_LOADER_ID
+    );
+
+    // A zero-length dispute window lets the happy path withdraw right after finalizing
+    await program.methods
+      .initializeConfig(authority.publicKey, new anchor.BN(0), 1_000, authority.publicKey)
+      .accounts({
+        admin: authority.publicKey,
+        config: configPda,
+        program: program.programId,
+        programData: programDataPda,
+        systemProgram: SystemProgram.programId
+      })
+      .rpc();
+
+    projectPda = projectPdaFor(projectId);
 
     projectVaultAta = getAssociatedTokenAddressSync(
       depositMint,
@@ -97,14 +257,7 @@ describe("funding_hub", () => {
     );
 
     badgeMintKeypair = anchor.web3.Keypair.generate();
-    [badgeMetadataPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("metadata"),
-        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
-        badgeMintKeypair.publicKey.toBuffer()
-      ],
-      TOKEN_METADATA_PROGRAM_ID
-    );
+    badgeMetadataPda = metadataPdaFor(badgeMintKeypair.publicKey);
 
     await program.methods
       .initializeProject(
@@ -126,20 +279,43 @@ describe("funding_hub", () => {
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-        rent: SYSVAR_RENT_PUBKEY
+        config: configPda,
+        rent: SYSVAR_RENT_PUBKEY,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
       })
       .signers([badgeMintKeypair])
       .rpc();
   });
 
+  it("rejects a badge metadata account that is not the badge mint's PDA", async () => {
+    // Metadata PDA of an unrelated mint, as a mis-built transaction would pass it
+    await expectInitializeProjectError(
+      new anchor.BN(101),
+      { badgeMetadata: metadataPdaFor(anchor.web3.Keypair.generate().publicKey) },
+      "MetadataAccountMismatch"
+    );
+  });
+
+  it("rejects a token metadata program other than Metaplex", async () => {
+    await expectInitializeProjectError(
+      new anchor.BN(102),
+      { tokenMetadataProgram: SystemProgram.programId },
+      "MetadataProgramMismatch"
+    );
+  });
+
   it("accepts pledges and mints donor badge", async () => {
     const donorBadgeAta = getAssociatedTokenAddressSync(
       badgeMintKeypair.publicKey,
       donor.publicKey
     );
+    const [donationPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("donation"), projectPda.toBuffer(), donor.publicKey.toBuffer()],
+      program.programId
+    );
 
     await program.methods
-      .pledge(pledgeAmount)
+      .pledge(pledgeAmount, null)
       .accounts({
         donor: donor.publicKey,
         donorTokenAccount: donorDepositAta,
@@ -147,6 +323,15 @@ describe("funding_hub", () => {
         projectVault: projectVaultAta,
         badgeMint: badgeMintKeypair.publicKey,
         donorBadgeAccount: donorBadgeAta,
+        donation: donationPda,
+        referral: null,
+        rewardTier: null,
+        badgeConfig: null,
+        donorBadgeMint: null,
+        donorBadgeMetadata: null,
+        tokenMetadataProgram: null,
+        sysvarInstructions: null,
+        config: configPda,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -160,14 +345,439 @@ describe("funding_hub", () => {
 
     const projectAccount = await program.account.project.fetch(projectPda);
     assert.strictEqual(projectAccount.totalPledged.toNumber(), pledgeAmount.toNumber());
+
+    const donationAccount = await program.account.donation.fetch(donationPda);
+    assert.strictEqual(donationAccount.amount.toNumber(), pledgeAmount.toNumber());
+    assert.strictEqual(
+      donationAccount.weightedAmount.toNumber(),
+      pledgeAmount.toNumber(),
+      "pledges outside an early-bird window count 1:1"
+    );
+  });
+
+  it("rejects withdraw once a payout split is configured", async () => {
+    const splitProjectId = new anchor.BN(103);
+    const splitProject = projectPdaFor(splitProjectId);
+    const splitBadgeMint = anchor.web3.Keypair.generate();
+
+    await program.methods
+      .initializeProject(splitProjectId, targetAmount, deadlineTs, "Split", "SPLIT", "https://example.com/split.json")
+      .accounts(initializeProjectAccounts(splitProjectId, splitBadgeMint.publicKey))
+      .signers([splitBadgeMint])
+      .rpc();
+
+    const [payoutSplitPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("payout_split"), splitProject.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .setBeneficiaries([{ wallet: donor.publicKey, shareBps: 10_000 }])
+      .accounts({
+        authority: authority.publicKey,
+        project: splitProject,
+        payoutSplit: payoutSplitPda,
+        config: configPda,
+        systemProgram: SystemProgram.programId
+      })
+      .rpc();
+
+    await assert.rejects(
+      program.methods
+        .withdraw(new anchor.BN(1))
+        .accounts({
+          authority: authority.publicKey,
+          project: splitProject,
+          projectVault: getAssociatedTokenAddressSync(depositMint, splitProject, true),
+          authorityTokenAccount: authorityDepositAta,
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .rpc(),
+      (err) => {
+        assert.strictEqual(err.error.errorCode.code, "PayoutSplitActive");
+        return true;
+      }
+    );
+  });
+
+  describe("pledge vouchers", () => {
+    const voucherProjectId = new anchor.BN(104);
+    const voucherProject = projectPdaFor(voucherProjectId);
+    const voucherVault = () => getAssociatedTokenAddressSync(depositMint, voucherProject, true);
+    const voucherBadgeMint = anchor.web3.Keypair.generate();
+    const partnerSigner = anchor.web3.Keypair.generate();
+    const voucherDonor = anchor.web3.Keypair.generate();
+    let partnerPda;
+    let partnerFundingAta;
+
+    const voucherMessage = (amount, nonce) =>
+      Buffer.concat([
+        voucherProject.toBuffer(),
+        voucherDonor.publicKey.toBuffer(),
+        Buffer.from(new anchor.BN(amount).toArray("le", 8)),
+        Buffer.from(new anchor.BN(nonce).toArray("le", 8))
+      ]);
+
+    const donationPda = () =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("donation"), voucherProject.toBuffer(), voucherDonor.publicKey.toBuffer()],
+        program.programId
+      )[0];
+
+    // `signature` places the partner's Ed25519 instruction before or after the
+    // redeem, or leaves it out
+    const redeemVoucher = ({ amount, nonce, signer = partnerSigner, signedAmount = amount, signature = "before" }) => {
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message: voucherMessage(signedAmount, nonce)
+      });
+      const builder = program.methods
+        .redeemPledgeVoucher(new anchor.BN(amount), new anchor.BN(nonce))
+        .accounts({
+          payer: authority.publicKey,
+          donor: voucherDonor.publicKey,
+          partner: partnerPda,
+          partnerFundingAccount: partnerFundingAta,
+          project: voucherProject,
+          projectVault: voucherVault(),
+          badgeMint: voucherBadgeMint.publicKey,
+          donorBadgeAccount: getAssociatedTokenAddressSync(voucherBadgeMint.publicKey, voucherDonor.publicKey),
+          donation: donationPda(),
+          voucherNonce: PublicKey.findProgramAddressSync(
+            [Buffer.from("voucher"), partnerPda.toBuffer(), Buffer.from(new anchor.BN(nonce).toArray("le", 8))],
+            program.programId
+          )[0],
+          badgeConfig: null,
+          donorBadgeMint: null,
+          donorBadgeMetadata: null,
+          tokenMetadataProgram: null,
+          config: configPda,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID
+        });
+      if (signature === "before") return builder.preInstructions([ed25519Ix]).rpc();
+      if (signature === "after") return builder.postInstructions([ed25519Ix]).rpc();
+      return builder.rpc();
+    };
+
+    const expectVoucherError = (attempt, errorCode) =>
+      assert.rejects(attempt, (err) => {
+        assert.strictEqual(err.error.errorCode.code, errorCode);
+        return true;
+      });
+
+    before(async () => {
+      await program.methods
+        .initializeProject(voucherProjectId, targetAmount, deadlineTs, "Voucher", "VOUCH", "https://example.com/voucher.json")
+        .accounts(initializeProjectAccounts(voucherProjectId, voucherBadgeMint.publicKey))
+        .signers([voucherBadgeMint])
+        .rpc();
+
+      [partnerPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("partner"), partnerSigner.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .registerPartner(partnerSigner.publicKey)
+        .accounts({
+          admin: authority.publicKey,
+          config: configPda,
+          partner: partnerPda,
+          systemProgram: SystemProgram.programId
+        })
+        .rpc();
+
+      // The partner funds redemptions from its own account, delegated to its PDA
+      partnerFundingAta = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          authority.payer,
+          depositMint,
+          partnerSigner.publicKey
+        )
+      ).address;
+      await mintTo(provider.connection, authority.payer, depositMint, partnerFundingAta, authority.publicKey, 10_000);
+      await approve(provider.connection, authority.payer, partnerFundingAta, partnerPda, partnerSigner, 1_000);
+    });
+
+    it("credits the donor for a partner-signed voucher", async () => {
+      await redeemVoucher({ amount: 400, nonce: 1 });
+
+      const donationAccount = await program.account.donation.fetch(donationPda());
+      assert.strictEqual(donationAccount.amount.toNumber(), 400);
+      const donorBadge = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(voucherBadgeMint.publicKey, voucherDonor.publicKey)
+      );
+      assert.strictEqual(Number(donorBadge.amount), 1, "badge not minted");
+
+      const partnerAccount = await program.account.partner.fetch(partnerPda);
+      assert.strictEqual(partnerAccount.totalRedeemed.toNumber(), 400);
+      const vault = await getAccount(provider.connection, voucherVault());
+      assert.strictEqual(Number(vault.amount), 400);
+      const funding = await getAccount(provider.connection, partnerFundingAta);
+      assert.strictEqual(Number(funding.amount), 9_600);
+    });
+
+    it("rejects a voucher signed by another key", async () => {
+      await expectVoucherError(
+        redeemVoucher({ amount: 100, nonce: 2, signer: anchor.web3.Keypair.generate() }),
+        "InvalidVoucherSignature"
+      );
+    });
+
+    it("rejects a voucher whose amount was tampered with", async () => {
+      await expectVoucherError(
+        redeemVoucher({ amount: 500, nonce: 3, signedAmount: 100 }),
+        "InvalidVoucherSignature"
+      );
+    });
+
+    it("rejects a voucher without an Ed25519 instruction right before it", async () => {
+      await expectVoucherError(redeemVoucher({ amount: 100, nonce: 4, signature: "none" }), "MissingVoucherSignature");
+      await expectVoucherError(redeemVoucher({ amount: 100, nonce: 4, signature: "after" }), "MissingVoucherSignature");
+    });
+
+    it("rejects a replayed voucher nonce", async () => {
+      // The nonce account from the first redemption already exists
+      await assert.rejects(redeemVoucher({ amount: 400, nonce: 1 }));
+
+      const partnerAccount = await program.account.partner.fetch(partnerPda);
+      assert.strictEqual(partnerAccount.totalRedeemed.toNumber(), 400);
+    });
+
+    it("rejects a voucher above the partner's remaining allowance", async () => {
+      await expectVoucherError(redeemVoucher({ amount: 700, nonce: 5 }), "VoucherExceedsAllowance");
+    });
+  });
+
+  describe("reward tiers", () => {
+    const tierId = 1;
+
+    it("limits each tier to its supply and each backer to one perk", async () => {
+      const side = await createProject(new anchor.BN(108), 10_000);
+      const rewardTier = PublicKey.findProgramAddressSync(
+        [Buffer.from("reward_tier"), side.project.toBuffer(), Buffer.from([tierId])],
+        program.programId
+      )[0];
+      await program.methods
+        .createRewardTier(tierId, new anchor.BN(500), 2, Array(32).fill(3))
+        .accounts({
+          authority: authority.publicKey,
+          project: side.project,
+          rewardTier,
+          config: configPda,
+          systemProgram: SystemProgram.programId
+        })
+        .rpc();
+
+      const first = await fundedDonor(1_000);
+      const second = await fundedDonor(500);
+      const third = await fundedDonor(500);
+      const plain = await fundedDonor(500);
+
+      await expectError(pledgeTo(side, first, 499, rewardTier), "PledgeBelowRewardPrice");
+      await pledgeTo(side, first, 500, rewardTier);
+      await expectError(pledgeTo(side, first, 500, rewardTier), "RewardAlreadyClaimed");
+      await pledgeTo(side, second, 500, rewardTier);
+      await expectError(pledgeTo(side, third, 500, rewardTier), "RewardTierSoldOut");
+      await pledgeTo(side, plain, 500);
+
+      let tier = await program.account.rewardTier.fetch(rewardTier);
+      assert.strictEqual(tier.claimed, 2);
+      const donation = await program.account.donation.fetch(donationPdaFor(side.project, first.keypair.publicKey));
+      assert.strictEqual(donation.rewardTier, tierId);
+      assert.strictEqual(donation.rewardFulfilled, false);
+
+      const markFulfilled = (donor) =>
+        program.methods
+          .markRewardFulfilled()
+          .accounts({
+            authority: authority.publicKey,
+            project: side.project,
+            rewardTier,
+            donation: donationPdaFor(side.project, donor.keypair.publicKey),
+            config: configPda
+          })
+          .rpc();
+
+      await markFulfilled(first);
+      await expectError(markFulfilled(first), "RewardAlreadyFulfilled");
+      await expectError(markFulfilled(plain), "NoRewardClaimed");
+      tier = await program.account.rewardTier.fetch(rewardTier);
+      assert.strictEqual(tier.fulfilled, 1);
+    });
+  });
+
+  describe("disputes", () => {
+    const reasonHash = Array(32).fill(7);
+    const disputePdaFor = (project) =>
+      PublicKey.findProgramAddressSync([Buffer.from("dispute"), project.toBuffer()], program.programId)[0];
+
+    const setDisputeConfig = (windowSecs, thresholdBps) =>
+      program.methods
+        .updateConfig(authority.publicKey, new anchor.BN(windowSecs), thresholdBps)
+        .accounts({ admin: authority.publicKey, config: configPda })
+        .rpc();
+
+    const openDispute = (project, donor) =>
+      program.methods
+        .openDispute(reasonHash)
+        .accounts({
+          donor: donor.keypair.publicKey,
+          project,
+          donation: donationPdaFor(project, donor.keypair.publicKey),
+          dispute: disputePdaFor(project),
+          config: configPda,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([donor.keypair])
+        .rpc();
+
+    const backDispute = (project, donor) =>
+      program.methods
+        .backDispute()
+        .accounts({
+          donor: donor.keypair.publicKey,
+          project,
+          donation: donationPdaFor(project, donor.keypair.publicKey),
+          dispute: disputePdaFor(project),
+          config: configPda
+        })
+        .signers([donor.keypair])
+        .rpc();
+
+    const resolveDispute = (project, refundDonors, arbiter = null) =>
+      program.methods
+        .resolveDispute(refundDonors, Array(32).fill(9))
+        .accounts({
+          arbiter: arbiter ? arbiter.publicKey : authority.publicKey,
+          config: configPda,
+          project,
+          dispute: disputePdaFor(project)
+        })
+        .signers(arbiter ? [arbiter] : [])
+        .rpc();
+
+    const refund = ({ project, vault }, donor) =>
+      program.methods
+        .refund()
+        .accounts({
+          donor: donor.keypair.publicKey,
+          project,
+          donation: donationPdaFor(project, donor.keypair.publicKey),
+          projectVault: vault,
+          donorTokenAccount: donor.tokenAccount,
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .signers([donor.keypair])
+        .rpc();
+
+    it("locks payouts while backed donors dispute and refunds them on the arbiter's ruling", async () => {
+      const side = await createProject(new anchor.BN(106), 1_000);
+      const opener = await fundedDonor(300);
+      const backer = await fundedDonor(700);
+      await pledgeTo(side, opener, 300);
+      await pledgeTo(side, backer, 700);
+
+      // The window is fixed at finalization, so the default can come back right after
+      await setDisputeConfig(3_600, 5_000);
+      try {
+        await finalize(side.project);
+      } finally {
+        await setDisputeConfig(0, 1_000);
+      }
+
+      await openDispute(side.project, opener);
+      let project = await program.account.project.fetch(side.project);
+      assert.deepStrictEqual(project.disputeStatus, { pending: {} }, "30% backing is below the 50% threshold");
+      await expectError(withdrawFrom(side, 100), "PayoutsLocked");
+      await expectError(backDispute(side.project, opener), "DisputeAlreadyBacked");
+      await expectError(resolveDispute(side.project, true), "NoOpenDispute");
+
+      await backDispute(side.project, backer);
+      project = await program.account.project.fetch(side.project);
+      assert.deepStrictEqual(project.disputeStatus, { open: {} });
+      const dispute = await program.account.dispute.fetch(disputePdaFor(side.project));
+      assert.strictEqual(dispute.backingWeight.toNumber(), 1_000);
+      assert.strictEqual(dispute.backers, 2);
+
+      await expectError(resolveDispute(side.project, true, opener.keypair), "Unauthorized");
+      await resolveDispute(side.project, true);
+      project = await program.account.project.fetch(side.project);
+      assert.deepStrictEqual(project.status, { refunding: {} });
+      assert.deepStrictEqual(project.disputeStatus, { refunded: {} });
+      await expectError(withdrawFrom(side, 100), "ProjectNotSuccessful");
+
+      await refund(side, opener);
+      const openerAccount = await getAccount(provider.connection, opener.tokenAccount);
+      assert.strictEqual(Number(openerAccount.amount), 300);
+      await expectError(refund(side, opener), "AlreadyRefunded");
+    });
+
+    it("rejects a dispute once the window has closed", async () => {
+      const side = await createProject(new anchor.BN(107), 500);
+      const late = await fundedDonor(500);
+      await pledgeTo(side, late, 500);
+      await finalize(side.project);
+
+      await expectError(openDispute(side.project, late), "DisputeWindowClosed");
+      await expectError(refund(side, late), "ProjectNotRefundable");
+      await withdrawFrom(side, 500);
+    });
+  });
+
+  it("caps withdrawals at what was raised and sweeps only unaccounted surplus", async () => {
+    const side = await createProject(new anchor.BN(105), 1_000);
+    await pledgeTo(side, await fundedDonor(1_000), 1_000);
+    await finalize(side.project);
+
+    await withdrawFrom(side, 400);
+    await expectError(withdrawFrom(side, 700), "WithdrawalExceedsRaised");
+    let project = await program.account.project.fetch(side.project);
+    assert.strictEqual(project.totalWithdrawn.toNumber(), 400);
+
+    const sweep = () =>
+      program.methods
+        .sweepSurplus()
+        .accounts({
+          authority: authority.publicKey,
+          project: side.project,
+          projectVault: side.vault,
+          authorityTokenAccount: authorityDepositAta,
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID
+        })
+        .rpc();
+    await expectError(sweep(), "NoSurplus");
+
+    // A stray transfer into the vault is surplus, not pledges
+    await mintTo(provider.connection, authority.payer, depositMint, side.vault, authority.publicKey, 50);
+    const before = await getAccount(provider.connection, authorityDepositAta);
+    await sweep();
+    const after = await getAccount(provider.connection, authorityDepositAta);
+    assert.strictEqual(Number(after.amount - before.amount), 50);
+    await expectError(sweep(), "NoSurplus");
+
+    // The pledged remainder is still withdrawable in full
+    await withdrawFrom(side, 600);
+    project = await program.account.project.fetch(side.project);
+    assert.strictEqual(project.totalWithdrawn.toNumber(), 1_000);
+    const vault = await getAccount(provider.connection, side.vault);
+    assert.strictEqual(Number(vault.amount), 0);
   });
 
   it("finalizes and withdraws funds", async () => {
+    const before = Number((await getAccount(provider.connection, authorityDepositAta)).amount);
     await program.methods
       .finalizeProject()
       .accounts({
         authority: authority.publicKey,
-        project: projectPda
+        project: projectPda,
+        config: configPda
       })
       .rpc();
 
@@ -178,11 +788,12 @@ describe("funding_hub", () => {
         project: projectPda,
         projectVault: projectVaultAta,
         authorityTokenAccount: authorityDepositAta,
+        config: configPda,
         tokenProgram: TOKEN_PROGRAM_ID
       })
       .rpc();
 
     const authorityAccount = await getAccount(provider.connection, authorityDepositAta);
-    assert.strictEqual(Number(authorityAccount.amount), pledgeAmountNumber);
+    assert.strictEqual(Number(authorityAccount.amount), before + pledgeAmount.toNumber());
   });
 });
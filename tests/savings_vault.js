@@ -0,0 +1,300 @@
+const anchor = require("@coral-xyz/anchor");
+const assert = require("assert");
+const {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  getAccount,
+  getAssociatedTokenAddressSync
+} = require("@solana/spl-token");
+
+const {
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  PublicKey,
+  LAMPORTS_PER_SOL
+} = anchor.web3;
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+const BPF_UPGRADEABLE_LOADER_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+describe("savings_vault", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.SavingsVault;
+  const fundingHub = anchor.workspace.FundingHub;
+  const authority = provider.wallet;
+  const connection = provider.connection;
+
+  const principal = 1_000_000_000_000;
+  const vaultId = Buffer.from("yield001");
+
+  let depositMint;
+  let rewardMint;
+  let saver;
+  let saverDepositAta;
+  let saverRewardAta;
+  let vaultPda;
+  let depositPda;
+  let configPda;
+  let fundingConfigPda;
+  let targetProject;
+  let targetBadgeMint;
+  let wrongMintProject;
+
+  const pda = (seeds, programId) => PublicKey.findProgramAddressSync(seeds, programId)[0];
+
+  const metadataPdaFor = (mint) =>
+    pda([Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()], TOKEN_METADATA_PROGRAM_ID);
+
+  const initializeConfigIfMissing = async (configProgram, config, args) => {
+    if (await connection.getAccountInfo(config)) return;
+    await configProgram.methods
+      .initializeConfig(...args)
+      .accounts({
+        admin: authority.publicKey,
+        config,
+        program: configProgram.programId,
+        programData: pda([configProgram.programId.toBuffer()], BPF_UPGRADEABLE_LOADER_ID),
+        systemProgram: SystemProgram.programId
+      })
+      .rpc();
+  };
+
+  const createProject = async (id, mint) => {
+    const projectId = new anchor.BN(id);
+    const project = pda(
+      [Buffer.from("project"), authority.publicKey.toBuffer(), Buffer.from(projectId.toArray("le", 8))],
+      fundingHub.programId
+    );
+    const badgeMint = anchor.web3.Keypair.generate();
+    await fundingHub.methods
+      .initializeProject(
+        projectId,
+        new anchor.BN(1_000_000),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 600),
+        "Yield",
+        "YIELD",
+        "https://example.com/yield.json"
+      )
+      .accounts({
+        authority: authority.publicKey,
+        project,
+        mint,
+        projectVault: getAssociatedTokenAddressSync(mint, project, true),
+        badgeMint: badgeMint.publicKey,
+        badgeMetadata: metadataPdaFor(badgeMint.publicKey),
+        config: fundingConfigPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
+      })
+      .signers([badgeMint])
+      .rpc();
+    return { project, badgeMint: badgeMint.publicKey };
+  };
+
+  const setYieldTarget = (project) =>
+    program.methods
+      .setYieldTarget()
+      .accounts({
+        owner: saver.publicKey,
+        deposit: depositPda,
+        vault: vaultPda,
+        project,
+        config: configPda
+      })
+      .signers([saver])
+      .rpc();
+
+  const setPauseFlags = (paused) =>
+    program.methods
+      .setPauseFlags(paused)
+      .accounts({ guardian: authority.publicKey, config: configPda })
+      .rpc();
+
+  const deposit = (amount) =>
+    program.methods
+      .deposit(new anchor.BN(amount))
+      .accounts({
+        user: saver.publicKey,
+        vault: vaultPda,
+        deposit: depositPda,
+        userTokenAccount: saverDepositAta,
+        vaultTokenAccount: getAssociatedTokenAddressSync(depositMint, vaultPda, true),
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .signers([saver])
+      .rpc();
+
+  const expectError = (attempt, errorCode) =>
+    assert.rejects(attempt, (err) => {
+      assert.strictEqual(err.error.errorCode.code, errorCode);
+      return true;
+    });
+
+  before(async () => {
+    depositMint = await createMint(connection, authority.payer, authority.publicKey, null, 6);
+    rewardMint = await createMint(connection, authority.payer, authority.publicKey, null, 6);
+
+    saver = anchor.web3.Keypair.generate();
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(saver.publicKey, 2 * LAMPORTS_PER_SOL)
+    );
+    saverDepositAta = (
+      await getOrCreateAssociatedTokenAccount(connection, authority.payer, depositMint, saver.publicKey)
+    ).address;
+    saverRewardAta = (
+      await getOrCreateAssociatedTokenAccount(connection, authority.payer, rewardMint, saver.publicKey)
+    ).address;
+    await mintTo(connection, authority.payer, depositMint, saverDepositAta, authority.publicKey, principal + 1_000);
+
+    // funding_hub's tests may already have set up its config on this validator
+    fundingConfigPda = pda([Buffer.from("config")], fundingHub.programId);
+    await initializeConfigIfMissing(fundingHub, fundingConfigPda, [
+      authority.publicKey,
+      new anchor.BN(0),
+      1_000,
+      authority.publicKey
+    ]);
+    ({ project: targetProject, badgeMint: targetBadgeMint } = await createProject(201, rewardMint));
+    ({ project: wrongMintProject } = await createProject(202, depositMint));
+
+    configPda = pda([Buffer.from("config")], program.programId);
+    await initializeConfigIfMissing(program, configPda, [authority.publicKey]);
+
+    vaultPda = pda([Buffer.from("vault"), authority.publicKey.toBuffer(), vaultId], program.programId);
+    // A one-slot term at 100% APY pays a small but non-zero reward
+    await program.methods
+      .initializeVault([...vaultId], new anchor.BN(1), 10_000)
+      .accounts({
+        authority: authority.publicKey,
+        vault: vaultPda,
+        depositMint,
+        rewardMint,
+        vaultTokenAccount: getAssociatedTokenAddressSync(depositMint, vaultPda, true),
+        rewardVault: getAssociatedTokenAddressSync(rewardMint, vaultPda, true),
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .rpc();
+    await mintTo(
+      connection,
+      authority.payer,
+      rewardMint,
+      getAssociatedTokenAddressSync(rewardMint, vaultPda, true),
+      authority.publicKey,
+      1_000_000
+    );
+
+    depositPda = pda([Buffer.from("deposit"), vaultPda.toBuffer(), saver.publicKey.toBuffer()], program.programId);
+    await deposit(principal);
+  });
+
+  it("rejects a yield target whose mint is not the vault's reward mint", async () => {
+    await expectError(setYieldTarget(wrongMintProject), "MintMismatch");
+  });
+
+  it("lets the saver clear the yield target while deposits are paused", async () => {
+    await setYieldTarget(targetProject);
+    await setPauseFlags(2); // PAUSE_DEPOSITS
+    try {
+      await expectError(setYieldTarget(targetProject), "DepositsPaused");
+      await setYieldTarget(null);
+      const receipt = await program.account.depositReceipt.fetch(depositPda);
+      assert.ok(receipt.yieldTarget.equals(PublicKey.default));
+    } finally {
+      await setPauseFlags(0);
+    }
+  });
+
+  it("pledges the reward to the target and returns the principal to the saver", async () => {
+    await setYieldTarget(targetProject);
+
+    // Wait out the one-slot term
+    const { unlockSlot } = await program.account.depositReceipt.fetch(depositPda);
+    while ((await connection.getSlot("confirmed")) < unlockSlot.toNumber()) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+
+    const donationPda = pda(
+      [Buffer.from("donation"), targetProject.toBuffer(), saver.publicKey.toBuffer()],
+      fundingHub.programId
+    );
+    const saverBadgeAta = getAssociatedTokenAddressSync(targetBadgeMint, saver.publicKey);
+    const projectVault = getAssociatedTokenAddressSync(rewardMint, targetProject, true);
+
+    await program.methods
+      .claimAndPledge()
+      .accounts({
+        claim: {
+          user: saver.publicKey,
+          vault: vaultPda,
+          deposit: depositPda,
+          vaultTokenAccount: getAssociatedTokenAddressSync(depositMint, vaultPda, true),
+          rewardVault: getAssociatedTokenAddressSync(rewardMint, vaultPda, true),
+          userTokenAccount: saverDepositAta,
+          userRewardAccount: saverRewardAta,
+          config: configPda,
+          tokenProgram: TOKEN_PROGRAM_ID
+        },
+        project: targetProject,
+        projectVault,
+        badgeMint: targetBadgeMint,
+        donorBadgeAccount: saverBadgeAta,
+        donation: donationPda,
+        fundingConfig: fundingConfigPda,
+        badgeConfig: null,
+        donorBadgeMint: null,
+        donorBadgeMetadata: null,
+        tokenMetadataProgram: null,
+        sysvarInstructions: null,
+        fundingHubProgram: fundingHub.programId,
+        systemProgram: SystemProgram.programId,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .signers([saver])
+      .rpc();
+
+    const vaultAccount = await getAccount(connection, projectVault);
+    const reward = Number(vaultAccount.amount);
+    assert.ok(reward > 0, "reward not pledged");
+
+    const donation = await fundingHub.account.donation.fetch(donationPda);
+    assert.ok(donation.donor.equals(saver.publicKey));
+    assert.strictEqual(donation.amount.toNumber(), reward);
+    const badge = await getAccount(connection, saverBadgeAta);
+    assert.strictEqual(Number(badge.amount), 1, "badge not minted to the saver");
+
+    const principalBack = await getAccount(connection, saverDepositAta);
+    assert.strictEqual(Number(principalBack.amount), principal + 1_000);
+    const rewardLeft = await getAccount(connection, saverRewardAta);
+    assert.strictEqual(Number(rewardLeft.amount), 0);
+  });
+
+  it("clears the yield target when a claimed receipt is reopened", async () => {
+    await deposit(1_000);
+
+    const receipt = await program.account.depositReceipt.fetch(depositPda);
+    assert.strictEqual(receipt.claimed, false);
+    assert.ok(receipt.yieldTarget.equals(PublicKey.default));
+  });
+});